@@ -0,0 +1,235 @@
+#!/usr/bin/env python3
+"""Synthesises every sound the game plays into this directory, using only the standard library.
+
+Run it from anywhere with `python3 assets/audio/synth.py`. The output is deterministic, so
+re-running it only changes the files if this script changes.
+"""
+import math
+import os
+import random
+import struct
+import wave
+
+RATE = 22050
+TEMPO = 120
+BEAT = 60.0 / TEMPO
+# Every stem is four bars of four beats, so they loop in step with each other.
+LOOP_BEATS = 16
+LOOP_LENGTH = LOOP_BEATS * BEAT
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+
+# A minor: Am, F, C, G, one bar each.
+CHORDS = [
+    [57, 60, 64],
+    [53, 57, 60],
+    [48, 52, 55],
+    [55, 59, 62],
+]
+
+
+def frequency(note):
+    return 440.0 * 2.0 ** ((note - 69) / 12.0)
+
+
+def silence(seconds):
+    return [0.0] * int(seconds * RATE)
+
+
+def envelope(index, length, attack, release):
+    """Linear attack and release, in samples, around a held middle."""
+    if index < attack:
+        return index / attack
+    if index > length - release:
+        return max(0.0, (length - index) / release)
+    return 1.0
+
+
+def add(buffer, start, samples, gain=1.0):
+    """Mixes `samples` into `buffer` from `start` seconds, wrapping round so loops stay seamless."""
+    offset = int(start * RATE)
+    for index, sample in enumerate(samples):
+        buffer[(offset + index) % len(buffer)] += sample * gain
+
+
+def tone(note, seconds, shape="sine", attack=0.01, release=0.1, detune=0.0):
+    length = int(seconds * RATE)
+    step = frequency(note) * (1.0 + detune) / RATE
+    attack, release = max(1, int(attack * RATE)), max(1, int(release * RATE))
+    samples = []
+    phase = 0.0
+    for index in range(length):
+        if shape == "sine":
+            value = math.sin(2.0 * math.pi * phase)
+        elif shape == "triangle":
+            value = 4.0 * abs(phase - 0.5) - 1.0
+        else:
+            # A softened saw: the first few harmonics only, so it doesn't alias.
+            value = sum(math.sin(2.0 * math.pi * phase * h) / h for h in range(1, 6)) * 0.6
+        samples.append(value * envelope(index, length, attack, release))
+        phase = (phase + step) % 1.0
+    return samples
+
+
+def pluck(note, seconds, decay=6.0):
+    samples = tone(note, seconds, "triangle", attack=0.005, release=0.05)
+    return [s * math.exp(-decay * i / RATE) for i, s in enumerate(samples)]
+
+
+def noise(seconds, rng, cutoff=0.2):
+    """White noise through a one-pole low-pass; lower `cutoff` is darker."""
+    value = 0.0
+    samples = []
+    for _ in range(int(seconds * RATE)):
+        value += cutoff * (rng.uniform(-1.0, 1.0) - value)
+        samples.append(value)
+    return samples
+
+
+def hit(seconds, rng, cutoff, decay):
+    return [s * math.exp(-decay * i / RATE) for i, s in enumerate(noise(seconds, rng, cutoff))]
+
+
+def kick():
+    length = int(0.25 * RATE)
+    samples = []
+    phase = 0.0
+    for index in range(length):
+        t = index / RATE
+        phase += (50.0 + 90.0 * math.exp(-30.0 * t)) / RATE
+        samples.append(math.sin(2.0 * math.pi * phase) * math.exp(-12.0 * t))
+    return samples
+
+
+def normalise(samples, peak):
+    loudest = max(abs(s) for s in samples) or 1.0
+    return [s * peak / loudest for s in samples]
+
+
+def write(path, channels):
+    """Writes 16 bit PCM; `channels` is one list per channel, all the same length."""
+    path = os.path.join(HERE, path)
+    os.makedirs(os.path.dirname(path), exist_ok=True)
+    with wave.open(path, "wb") as file:
+        file.setnchannels(len(channels))
+        file.setsampwidth(2)
+        file.setframerate(RATE)
+        frames = bytearray()
+        for frame in zip(*channels):
+            for sample in frame:
+                frames += struct.pack("<h", int(max(-1.0, min(1.0, sample)) * 32767))
+        file.writeframes(bytes(frames))
+
+
+def write_mono(path, samples):
+    write(path, [samples])
+
+
+def write_sides(path, samples):
+    """A left-only and a right-only copy, which the game plays together to pan the sound."""
+    quiet = [0.0] * len(samples)
+    write(path + "_left.wav", [samples, quiet])
+    write(path + "_right.wav", [quiet, samples])
+
+
+def pad(buffer, gain, detune=0.0, shape="sine"):
+    bar = 4 * BEAT
+    for index, chord in enumerate(CHORDS):
+        for note in chord:
+            add(buffer, index * bar, tone(note, bar, shape, 0.6, 0.6, detune), gain)
+
+
+def calm():
+    buffer = silence(LOOP_LENGTH)
+    pad(buffer, 0.25)
+    # A slow arpeggio an octave up, one note a beat.
+    for beat in range(LOOP_BEATS):
+        chord = CHORDS[beat // 4]
+        add(buffer, beat * BEAT, pluck(chord[beat % 3] + 12, BEAT * 2), 0.3)
+    return normalise(buffer, 0.6)
+
+
+def uneasy():
+    buffer = silence(LOOP_LENGTH)
+    # The same chords, slightly out of tune with themselves.
+    pad(buffer, 0.2)
+    pad(buffer, 0.15, detune=0.006)
+    # A pulsing root on every eighth note, and a semitone rub at the end of each bar.
+    for eighth in range(LOOP_BEATS * 2):
+        root = CHORDS[eighth // 8][0] - 12
+        add(buffer, eighth * BEAT / 2, pluck(root, BEAT / 2, decay=10.0), 0.35)
+    for bar in range(4):
+        add(buffer, (bar * 4 + 3) * BEAT, tone(CHORDS[bar][0] + 13, BEAT, "sine", 0.2, 0.3), 0.12)
+    return normalise(buffer, 0.6)
+
+
+def chased():
+    rng = random.Random(3)
+    buffer = silence(LOOP_LENGTH)
+    pad(buffer, 0.12, shape="saw")
+    for sixteenth in range(LOOP_BEATS * 4):
+        root = CHORDS[sixteenth // 16][0] - 12
+        accent = 0.4 if sixteenth % 4 == 0 else 0.25
+        add(buffer, sixteenth * BEAT / 4, pluck(root, BEAT / 4, decay=18.0), accent)
+        add(buffer, sixteenth * BEAT / 4, hit(0.05, rng, 0.9, 60.0), 0.08)
+    for beat in range(LOOP_BEATS):
+        add(buffer, beat * BEAT, kick(), 0.6)
+        if beat % 2 == 1:
+            add(buffer, beat * BEAT, hit(0.2, rng, 0.5, 20.0), 0.35)
+    return normalise(buffer, 0.6)
+
+
+def stinger(notes, step, length, shape):
+    buffer = silence(length)
+    for index, note in enumerate(notes):
+        samples = tone(note, length - index * step, shape, 0.005, 0.4)
+        add(buffer, index * step, [s * math.exp(-2.5 * i / RATE) for i, s in enumerate(samples)])
+    return normalise(buffer, 0.8)
+
+
+def crowd():
+    """Overlapping voices: bursts of filtered noise, like syllables, rising and falling."""
+    rng = random.Random(1)
+    length = 4.0
+    buffer = noise(length, rng, 0.05)
+    buffer = [s * 0.5 for s in buffer]
+    for _ in range(60):
+        syllable = rng.uniform(0.08, 0.25)
+        formant = rng.uniform(0.08, 0.2)
+        burst = noise(syllable, rng, formant)
+        size = len(burst)
+        burst = [s * math.sin(math.pi * i / size) for i, s in enumerate(burst)]
+        add(buffer, rng.uniform(0.0, length), burst, rng.uniform(0.3, 1.0))
+    return normalise(buffer, 0.7)
+
+
+def footstep():
+    rng = random.Random(2)
+    return normalise(hit(0.12, rng, 0.15, 40.0), 0.8)
+
+
+def door():
+    """A shop door bell: two chimes a third apart."""
+    buffer = silence(0.8)
+    for index, note in enumerate([88, 84]):
+        chime = tone(note, 0.8 - index * 0.15, "sine", 0.002, 0.2)
+        add(buffer, index * 0.15, [s * math.exp(-5.0 * i / RATE) for i, s in enumerate(chime)])
+    return normalise(buffer, 0.8)
+
+
+def main():
+    write_mono("music/calm.wav", calm())
+    write_mono("music/uneasy.wav", uneasy())
+    write_mono("music/chased.wav", chased())
+    # A sudden cluster for being seen, a rising major arpeggio for a win, a falling minor one
+    # for a loss.
+    write_mono("stingers/spotted.wav", stinger([62, 63, 68], 0.03, 1.0, "saw"))
+    write_mono("stingers/win.wav", stinger([60, 64, 67, 72], 0.12, 1.6, "triangle"))
+    write_mono("stingers/lose.wav", stinger([64, 60, 57, 52], 0.2, 1.8, "saw"))
+    write_sides("sfx/crowd", crowd())
+    write_sides("sfx/footstep", footstep())
+    write_sides("sfx/door", door())
+
+
+if __name__ == "__main__":
+    main()
@@ -0,0 +1,280 @@
+use bevy::{asset::LoadState, audio::AudioSink, prelude::*};
+
+use crate::{
+    camera::camera_follow,
+    components::{Follower, Player},
+    config::GameConfig,
+    gameplay::{in_game, GameplayStage},
+    resources::{RunStats, SpottedStingers, Suspicion},
+    sfx::{
+        entrance_doors, follower_footsteps, load_sounds, quiet_ambience, start_ambience,
+        track_crowd, update_ambience,
+    },
+    user_settings::UserSettings,
+    AppState,
+};
+
+/// The music and sound effects. Needs `DefaultPlugins`.
+pub struct AudioPlugin;
+
+impl Plugin for AudioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicMood>()
+            .add_startup_system(load_music)
+            .add_startup_system(load_stingers)
+            .add_startup_system(load_sounds)
+            .add_system(start_music)
+            .add_system(update_music.after(start_music))
+            .add_system(start_ambience)
+            .add_system(update_ambience.after(start_ambience))
+            .add_system_set_to_stage(
+                GameplayStage::AfterSimulation,
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(track_music_mood)
+                    .with_system(spotted_stinger)
+                    // Sounds are placed relative to the camera, so wait for it to move.
+                    .with_system(track_crowd.after(camera_follow))
+                    .with_system(follower_footsteps.after(camera_follow))
+                    .with_system(entrance_doors.after(camera_follow)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Game)
+                    .with_system(calm_music)
+                    .with_system(quiet_ambience),
+            )
+            .add_system_set(SystemSet::on_enter(AppState::Win).with_system(win_stinger))
+            .add_system_set(SystemSet::on_enter(AppState::Lose).with_system(lose_stinger));
+    }
+}
+
+/// Music layers, all looping together from startup so they stay in time; only their volumes
+/// change.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Mood {
+    #[default]
+    Calm,
+    Uneasy,
+    Chased,
+}
+
+impl Mood {
+    pub const ALL: [Mood; 3] = [Mood::Calm, Mood::Uneasy, Mood::Chased];
+
+    fn stem_path(&self) -> &'static str {
+        match self {
+            Mood::Calm => "audio/music/calm.wav",
+            Mood::Uneasy => "audio/music/uneasy.wav",
+            Mood::Chased => "audio/music/chased.wav",
+        }
+    }
+}
+
+/// Which stem the music should be fading towards.
+#[derive(Default)]
+pub struct MusicMood(pub Mood);
+
+struct MusicStem {
+    mood: Mood,
+    source: Handle<AudioSource>,
+    /// Only there once the stems have started.
+    sink: Option<Handle<AudioSink>>,
+    /// How loud the stem currently is, from 0 to 1 before the volume settings are applied.
+    level: f32,
+}
+
+/// The music stems, started together once they've all finished loading.
+pub struct Music {
+    stems: Vec<MusicStem>,
+    started: bool,
+}
+
+/// The one-shot stingers, loaded up front.
+pub struct Stingers {
+    spotted: Handle<AudioSource>,
+    win: Handle<AudioSource>,
+    lose: Handle<AudioSource>,
+}
+
+/// Fraction of full volume a stem fades by per second.
+const CROSSFADE_RATE: f32 = 0.5;
+/// Tension above which the music turns uneasy, and above which it's a chase.
+const UNEASY_TENSION: f32 = 0.3;
+const CHASED_TENSION: f32 = 0.65;
+
+/// Whether `source` has loaded and can be played. Bevy keeps anything played before its source
+/// has loaded queued until it does, so a sound whose file is missing would sit in the queue for
+/// good; nothing is played until this says so.
+pub fn source_loaded(asset_server: &AssetServer, source: &Handle<AudioSource>) -> bool {
+    asset_server.get_load_state(source) == LoadState::Loaded
+}
+
+/// Whether `source` is done loading, whether or not it could be.
+fn source_settled(asset_server: &AssetServer, source: &Handle<AudioSource>) -> bool {
+    matches!(
+        asset_server.get_load_state(source),
+        LoadState::Loaded | LoadState::Failed
+    )
+}
+
+pub fn load_music(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let stems = Mood::ALL
+        .into_iter()
+        .map(|mood| MusicStem {
+            mood,
+            source: asset_server.load(mood.stem_path()),
+            sink: None,
+            level: if mood == Mood::Calm { 1.0 } else { 0.0 },
+        })
+        .collect();
+    commands.insert_resource(Music {
+        stems,
+        started: false,
+    });
+}
+
+/// Starts every stem that could be loaded at once, so they stay in time and moving between the
+/// menu and a run only changes the mix. Missing stems are left out.
+pub fn start_music(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut music: ResMut<Music>,
+) {
+    if music.started
+        || !music
+            .stems
+            .iter()
+            .all(|stem| source_settled(&asset_server, &stem.source))
+    {
+        return;
+    }
+    music.started = true;
+    for stem in music.stems.iter_mut() {
+        if source_loaded(&asset_server, &stem.source) {
+            let sink = audio
+                .play_with_settings(stem.source.clone(), PlaybackSettings::LOOP.with_volume(0.0));
+            // A strong handle keeps the sink around so its volume can be changed later.
+            stem.sink = Some(audio_sinks.get_handle(sink));
+        }
+    }
+}
+
+pub fn load_stingers(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(Stingers {
+        spotted: asset_server.load("audio/stingers/spotted.wav"),
+        win: asset_server.load("audio/stingers/win.wav"),
+        lose: asset_server.load("audio/stingers/lose.wav"),
+    });
+}
+
+/// Picks the mood from how close the nearest follower is and how suspicious they are. Being in
+/// sight is always a chase.
+pub fn track_music_mood(
+    config: Res<GameConfig>,
+    stats: Res<RunStats>,
+    suspicion: Res<Suspicion>,
+    player_query: Query<&Transform, With<Player>>,
+    follower_query: Query<&Transform, With<Follower>>,
+    mut mood: ResMut<MusicMood>,
+) {
+    let player = player_query.single().translation.truncate();
+    let closest = follower_query
+        .iter()
+        .map(|follower| follower.translation.truncate().distance(player))
+        .fold(f32::MAX, f32::min);
+    // Followers within hearing range start to raise the tension, right up to touching distance.
+    let proximity = if config.follower_hearing_range > 0.0 {
+        (1.0 - closest / (config.follower_hearing_range * 2.0)).clamp(0.0, 1.0)
+    } else {
+        0.0
+    };
+    let tension = proximity.max(suspicion.0);
+
+    let next = if stats.currently_seen || tension >= CHASED_TENSION {
+        Mood::Chased
+    } else if tension >= UNEASY_TENSION {
+        Mood::Uneasy
+    } else {
+        Mood::Calm
+    };
+    if mood.0 != next {
+        mood.0 = next;
+    }
+}
+
+/// Back to calm music once a run is over.
+pub fn calm_music(mut mood: ResMut<MusicMood>) {
+    mood.0 = Mood::Calm;
+}
+
+/// Crossfades the stems towards the current mood and applies the music volume.
+pub fn update_music(
+    time: Res<Time>,
+    settings: Res<UserSettings>,
+    mood: Res<MusicMood>,
+    mut music: ResMut<Music>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let step = CROSSFADE_RATE * time.delta_seconds();
+    let gain = settings.music_gain();
+    for stem in music.stems.iter_mut() {
+        let target = if stem.mood == mood.0 { 1.0 } else { 0.0 };
+        stem.level = if stem.level < target {
+            (stem.level + step).min(target)
+        } else {
+            (stem.level - step).max(target)
+        };
+        // The sink only exists once the stem has started playing.
+        if let Some(sink) = stem.sink.as_ref().and_then(|sink| audio_sinks.get(sink)) {
+            sink.set_volume(stem.level * gain);
+        }
+    }
+}
+
+fn play_stinger(
+    asset_server: &AssetServer,
+    audio: &Audio,
+    settings: &UserSettings,
+    stinger: &Handle<AudioSource>,
+) {
+    if source_loaded(asset_server, stinger) {
+        audio.play_with_settings(
+            stinger.clone(),
+            PlaybackSettings::ONCE.with_volume(settings.music_gain()),
+        );
+    }
+}
+
+/// Plays the spotted stinger each time a follower newly catches sight of the player.
+pub fn spotted_stinger(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<UserSettings>,
+    stingers: Res<Stingers>,
+    stats: Res<RunStats>,
+    mut played: ResMut<SpottedStingers>,
+) {
+    if stats.times_spotted > played.0 {
+        play_stinger(&asset_server, &audio, &settings, &stingers.spotted);
+        played.0 = stats.times_spotted;
+    }
+}
+
+pub fn win_stinger(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<UserSettings>,
+    stingers: Res<Stingers>,
+) {
+    play_stinger(&asset_server, &audio, &settings, &stingers.win);
+}
+
+pub fn lose_stinger(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<UserSettings>,
+    stingers: Res<Stingers>,
+) {
+    play_stinger(&asset_server, &audio, &settings, &stingers.lose);
+}
@@ -0,0 +1,138 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{MainCamera, Player, Walker},
+    config::GameConfig,
+    gameplay::{Interpolated, SimulationClock},
+    generation::{MAP_HEIGHT, MAP_WIDTH},
+    input::PlayerInput,
+};
+
+/// Spring state for the main camera's follow.
+#[derive(Component, Default)]
+pub struct CameraController {
+    pub velocity: Vec2,
+}
+
+pub fn spawn_camera(mut commands: Commands) {
+    commands
+        .spawn_bundle(Camera2dBundle::default())
+        .insert(MainCamera)
+        .insert(CameraController::default());
+}
+
+/// Menus and splash screens are laid out around the origin at normal zoom, so bring the camera
+/// back from wherever the last run left it.
+pub fn reset_camera(
+    mut camera_query: Query<
+        (
+            &mut Transform,
+            &mut OrthographicProjection,
+            &mut CameraController,
+        ),
+        With<MainCamera>,
+    >,
+) {
+    for (mut transform, mut projection, mut controller) in &mut camera_query {
+        transform.translation.x = 0.0;
+        transform.translation.y = 0.0;
+        projection.scale = 1.0;
+        controller.velocity = Vec2::ZERO;
+    }
+}
+
+/// Half the size of the world area the camera shows. Bevy keeps the projection's edges in step
+/// with the window, so this stays right across resizes.
+pub fn half_view_size(projection: &OrthographicProjection) -> Vec2 {
+    Vec2::new(projection.right, projection.top) * projection.scale
+}
+
+/// Keeps the view inside the map, or centres the map on an axis where it's smaller than the
+/// view.
+fn clamp_to_map(center: Vec2, half_view: Vec2) -> Vec2 {
+    let clamp_axis = |value: f32, half: f32, size: f32| {
+        if half * 2.0 >= size {
+            size / 2.0
+        } else {
+            value.clamp(half, size - half)
+        }
+    };
+    Vec2::new(
+        clamp_axis(center.x, half_view.x, MAP_WIDTH),
+        clamp_axis(center.y, half_view.y, MAP_HEIGHT),
+    )
+}
+
+/// Zooms with the mouse wheel or right stick, never further out than the whole map.
+pub fn camera_zoom(
+    input: Res<PlayerInput>,
+    config: Res<GameConfig>,
+    mut camera_query: Query<&mut OrthographicProjection, With<MainCamera>>,
+) {
+    for mut projection in &mut camera_query {
+        let fit_map =
+            (MAP_WIDTH / (2.0 * projection.right)).min(MAP_HEIGHT / (2.0 * projection.top));
+        let max_zoom = config
+            .camera_zoom_max
+            .min(fit_map)
+            .max(config.camera_zoom_min);
+        let scale = projection.scale * (1.0 - config.camera_zoom_step).powf(input.zoom);
+        let scale = scale.clamp(config.camera_zoom_min, max_zoom);
+        // Only write when it changes, so the projection isn't flagged as changed every frame.
+        if scale != projection.scale {
+            projection.scale = scale;
+        }
+    }
+}
+
+/// Follows the player on a critically damped spring, so the camera eases in without
+/// overshooting and physics jitter is smoothed out, looking ahead in the direction of travel.
+#[allow(clippy::type_complexity)]
+pub fn camera_follow(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    clock: Res<SimulationClock>,
+    player_query: Query<(&Transform, &Interpolated, &Walker), With<Player>>,
+    mut camera_query: Query<
+        (
+            &mut Transform,
+            &OrthographicProjection,
+            &mut CameraController,
+        ),
+        (With<MainCamera>, Without<Player>),
+    >,
+) {
+    let (player, interpolated, walker) = player_query.single();
+    // Where the player is drawn, so the camera doesn't judder against them between steps.
+    let player = interpolated.at(clock.fraction()).unwrap_or(*player);
+    let (mut camera, projection, mut controller) = camera_query.single_mut();
+    let half_view = half_view_size(projection);
+    let target = clamp_to_map(
+        player.translation.truncate() + walker.velocity * config.camera_look_ahead,
+        half_view,
+    );
+
+    let omega = config.camera_stiffness;
+    let delta = time.delta_seconds();
+    let decay = (-omega * delta).exp();
+    let offset = camera.translation.truncate() - target;
+    let temp = (controller.velocity + omega * offset) * delta;
+    controller.velocity = (controller.velocity - omega * temp) * decay;
+    let position = clamp_to_map(target + (offset + temp) * decay, half_view);
+
+    camera.translation.x = position.x;
+    camera.translation.y = position.y;
+}
+
+/// The inverse of `Camera::world_to_viewport`: where in the world a point in the viewport
+/// (logical pixels, origin bottom-left) lies.
+pub fn viewport_to_world(
+    camera: &Camera,
+    camera_transform: &GlobalTransform,
+    viewport_position: Vec2,
+) -> Option<Vec2> {
+    let size = camera.logical_viewport_size()?;
+    let ndc = viewport_position / size * 2.0 - Vec2::ONE;
+    let ndc_to_world = camera_transform.compute_matrix() * camera.projection_matrix().inverse();
+    Some(ndc_to_world.project_point3(ndc.extend(0.0)).truncate())
+}
@@ -1,4 +1,6 @@
 use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::generation::Building;
 
@@ -7,6 +9,10 @@ pub struct Player {
     pub destination: Building,
 }
 
+/// On the player while they're hiding in a doorway, out of sight and unable to move.
+#[derive(Component)]
+pub struct Hidden;
+
 #[derive(Component)]
 pub struct Follower;
 
@@ -26,3 +32,50 @@ pub struct Spawner {
 pub struct Entrance {
     pub building_name: String,
 }
+
+/// The one camera, spawned at startup and shared by every screen.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Anything spawned for a run, removed when the game state is left.
+#[derive(Component)]
+pub struct GameEntity;
+
+/// The win/lose splash and stats, removed when the results screen is left.
+#[derive(Component)]
+pub struct ResultsEntity;
+
+/// Shared kinematics for the player, followers and bystanders. Controllers only say how fast
+/// they'd like to go in `target_velocity`; `movement::walk` eases `velocity` towards it using
+/// the configured acceleration and deceleration, and moves the agent.
+#[derive(Component, Debug, Default, Clone, Copy, Serialize, Deserialize)]
+pub struct Walker {
+    pub velocity: Vec2,
+    pub target_velocity: Vec2,
+}
+
+/// How an agent is drawn. Kept on the agent so its shape can be rebuilt for a saved run, since
+/// the lyon path itself can't be saved.
+#[derive(Component, Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Appearance {
+    pub sides: usize,
+    pub fill_color: Color,
+    pub stroke_color: Color,
+}
+
+impl Appearance {
+    pub fn shape(&self, radius: f32) -> RegularPolygon {
+        shapes::RegularPolygon {
+            sides: self.sides,
+            feature: shapes::RegularPolygonFeature::Radius(radius),
+            ..shapes::RegularPolygon::default()
+        }
+    }
+
+    pub fn draw_mode(&self) -> DrawMode {
+        DrawMode::Outlined {
+            fill_mode: FillMode::color(self.fill_color),
+            outline_mode: StrokeMode::new(self.stroke_color, 5.0),
+        }
+    }
+}
@@ -0,0 +1,241 @@
+use std::{fs, path::PathBuf};
+
+use bevy::asset::FileAssetIo;
+use serde::{Deserialize, Serialize};
+
+/// Read at startup unless another file is given with `--config <path>`. Relative to the same
+/// folder the asset server loads `assets/` from, so it's found whatever the working directory.
+pub const DEFAULT_CONFIG_PATH: &str = "assets/config.ron";
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Difficulty {
+    Easy,
+    #[default]
+    Normal,
+    Hard,
+    /// Whatever the `custom` section of the config file says. There's no editor for it in the
+    /// game; it's only set by editing `config.ron`.
+    Custom,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 4] = [
+        Difficulty::Easy,
+        Difficulty::Normal,
+        Difficulty::Hard,
+        Difficulty::Custom,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+            Difficulty::Custom => "Custom",
+        }
+    }
+
+    pub fn next(&self) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|d| d == self).unwrap();
+        Difficulty::ALL[(index + 1) % Difficulty::ALL.len()]
+    }
+
+    pub fn previous(&self) -> Difficulty {
+        let index = Difficulty::ALL.iter().position(|d| d == self).unwrap();
+        Difficulty::ALL[(index + Difficulty::ALL.len() - 1) % Difficulty::ALL.len()]
+    }
+}
+
+/// Everything a difficulty preset changes about a run. Any field left out of a preset in the
+/// config file takes Normal's value.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DifficultySettings {
+    pub bystander_count: usize,
+    pub follower_count: usize,
+    pub follower_speed: f32,
+    /// How strongly the follower heads straight for the player rather than wandering.
+    pub follower_smoothing: f32,
+    /// How far, in pixels, a follower can see the player.
+    pub follower_vision_range: f32,
+    /// Seconds before the run is lost for taking too long.
+    pub time_limit: f32,
+    /// Suspicion gained per second while a follower can see the player; the run is lost at 1.
+    pub suspicion_fill_rate: f32,
+    /// Suspicion lost per second while out of sight.
+    pub suspicion_drain_rate: f32,
+}
+
+/// Tuning for the whole game, kept in one place so it can be adjusted without hunting for
+/// literals. Any field left out of the config file keeps its default.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct GameConfig {
+    pub pixels_per_meter: f32,
+    pub player_speed: f32,
+    /// Player speed multipliers while holding sprint or sneak.
+    pub sprint_multiplier: f32,
+    pub sneak_multiplier: f32,
+    /// Stamina used per second of sprinting and recovered per second otherwise; a full bar is 1.
+    pub stamina_drain_rate: f32,
+    pub stamina_regen_rate: f32,
+    /// How quickly, in pixels per second squared, every agent speeds up towards and slows down
+    /// from the speed it's aiming for.
+    pub acceleration: f32,
+    pub deceleration: f32,
+    /// How tightly the camera springs after the player; higher catches up faster.
+    pub camera_stiffness: f32,
+    /// Seconds of the player's current velocity the camera looks ahead by. 0 turns it off.
+    pub camera_look_ahead: f32,
+    /// Closest and furthest the camera can zoom, as projection scales. Zooming out also stops
+    /// once the whole map fits on screen.
+    pub camera_zoom_min: f32,
+    pub camera_zoom_max: f32,
+    /// Fraction the zoom changes by per mouse-wheel notch.
+    pub camera_zoom_step: f32,
+    /// Whether the minimap marks where each follower was last on screen.
+    pub minimap_show_followers: bool,
+    /// How close, in pixels, an unseen follower has to be before an arrow points it out, and
+    /// how far its footsteps carry. 0 turns both off.
+    pub follower_hearing_range: f32,
+    /// How far from the middle of the screen, in pixels, the crowd and doors can be heard.
+    /// Follower footsteps carry as far as the follower hearing range instead.
+    pub sound_range: f32,
+    /// Half the width of the player, follower and bystander colliders, and the radius of their
+    /// shapes.
+    pub agent_size: f32,
+    /// Fraction of the physics velocity kept each frame for the player and followers, so
+    /// collisions don't leave them drifting.
+    pub velocity_damping: f32,
+    pub bystander_speed_min: f32,
+    pub bystander_speed_max: f32,
+    /// How strongly bystanders head for their destination rather than wandering.
+    pub bystander_focus_min: f32,
+    pub bystander_focus_max: f32,
+    pub easy: DifficultySettings,
+    pub normal: DifficultySettings,
+    pub hard: DifficultySettings,
+    /// Only set here; the menu can pick Custom but not change it.
+    pub custom: DifficultySettings,
+}
+
+/// The Normal preset.
+impl Default for DifficultySettings {
+    fn default() -> Self {
+        DifficultySettings {
+            bystander_count: 999,
+            follower_count: 1,
+            follower_speed: 100.0,
+            follower_smoothing: 2.5,
+            follower_vision_range: 600.0,
+            time_limit: 180.0,
+            suspicion_fill_rate: 0.25,
+            suspicion_drain_rate: 0.1,
+        }
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        let normal = DifficultySettings::default();
+        GameConfig {
+            pixels_per_meter: 50.0,
+            player_speed: 100.0,
+            sprint_multiplier: 1.6,
+            sneak_multiplier: 0.5,
+            stamina_drain_rate: 0.2,
+            stamina_regen_rate: 0.1,
+            acceleration: 600.0,
+            deceleration: 900.0,
+            camera_stiffness: 6.0,
+            camera_look_ahead: 0.4,
+            camera_zoom_min: 0.4,
+            camera_zoom_max: 1.5,
+            camera_zoom_step: 0.1,
+            minimap_show_followers: true,
+            follower_hearing_range: 350.0,
+            sound_range: 700.0,
+            agent_size: 10.0,
+            velocity_damping: 0.95,
+            bystander_speed_min: 0.75,
+            bystander_speed_max: 125.0,
+            bystander_focus_min: 1.0,
+            bystander_focus_max: 4.0,
+            easy: DifficultySettings {
+                bystander_count: 1400,
+                follower_speed: 85.0,
+                follower_smoothing: 1.5,
+                follower_vision_range: 400.0,
+                time_limit: 300.0,
+                suspicion_fill_rate: 0.15,
+                suspicion_drain_rate: 0.15,
+                ..normal.clone()
+            },
+            hard: DifficultySettings {
+                bystander_count: 600,
+                follower_count: 2,
+                follower_speed: 110.0,
+                follower_smoothing: 4.0,
+                follower_vision_range: 750.0,
+                time_limit: 120.0,
+                suspicion_fill_rate: 0.4,
+                suspicion_drain_rate: 0.05,
+            },
+            custom: normal.clone(),
+            normal,
+        }
+    }
+}
+
+impl GameConfig {
+    pub fn difficulty(&self, difficulty: Difficulty) -> &DifficultySettings {
+        match difficulty {
+            Difficulty::Easy => &self.easy,
+            Difficulty::Normal => &self.normal,
+            Difficulty::Hard => &self.hard,
+            Difficulty::Custom => &self.custom,
+        }
+    }
+
+    /// Loads the config named by `--config <path>`, or the default file. This runs before the
+    /// app (and its logger) exists, so problems go straight to stderr and fall back to the
+    /// built in defaults rather than stopping the game.
+    pub fn from_args() -> Self {
+        let path = config_path_from_args(std::env::args().skip(1))
+            .unwrap_or_else(|| FileAssetIo::get_base_path().join(DEFAULT_CONFIG_PATH));
+        let contents = match fs::read_to_string(&path) {
+            Ok(contents) => contents,
+            Err(error) => {
+                eprintln!(
+                    "Couldn't read config {}, using defaults: {}",
+                    path.display(),
+                    error
+                );
+                return GameConfig::default();
+            }
+        };
+        match ron::from_str(&contents) {
+            Ok(config) => config,
+            Err(error) => {
+                eprintln!(
+                    "Couldn't parse config {}, using defaults: {}",
+                    path.display(),
+                    error
+                );
+                GameConfig::default()
+            }
+        }
+    }
+}
+
+fn config_path_from_args(mut args: impl Iterator<Item = String>) -> Option<PathBuf> {
+    while let Some(arg) = args.next() {
+        if arg == "--config" {
+            return args.next().map(PathBuf::from);
+        }
+        if let Some(path) = arg.strip_prefix("--config=") {
+            return Some(PathBuf::from(path));
+        }
+    }
+    None
+}
@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{CollisionGroups, Restitution};
+
+use crate::{
+    components::{Appearance, Bystander},
+    config::{DifficultySettings, GameConfig},
+    gameplay::{
+        spawn_agent, AddSimulationSystems, AgentState, GameplaySystem, SimulationStage, SpawnSystem,
+    },
+    generation::generate_bystander,
+    resources::{GameRng, RunConfig},
+    save::ResumedRun,
+    systems::bystander_movement,
+    AppState,
+};
+
+/// The bystanders wandering between buildings, for the player to lose the followers among.
+pub struct CrowdPlugin;
+
+impl Plugin for CrowdPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Game).with_system(
+                spawn_crowd
+                    .label(SpawnSystem::Crowd)
+                    .after(SpawnSystem::Player),
+            ),
+        )
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new().with_system(bystander_movement.label(GameplaySystem::Ai)),
+        );
+    }
+}
+
+fn spawn_crowd(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    resumed: Option<Res<ResumedRun>>,
+) {
+    if let Some(resumed) = resumed {
+        for saved in &resumed.0.bystanders {
+            create_bystander(
+                &mut commands,
+                &config,
+                saved.bystander(run_config.map),
+                &saved.agent,
+            );
+        }
+        return;
+    }
+    let settings = config.difficulty(run_config.difficulty);
+    create_bystanders(&mut commands, &mut rng, &config, settings, run_config.map);
+}
+
+fn create_bystanders(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    config: &GameConfig,
+    settings: &DifficultySettings,
+    map: usize,
+) {
+    (0..settings.bystander_count).for_each(|_| {
+        let bystander = generate_bystander(&mut rng.0, config, map);
+        let appearance = Appearance {
+            sides: bystander.side_count,
+            fill_color: bystander.fill_color,
+            stroke_color: bystander.stroke_color,
+        };
+        create_bystander(
+            commands,
+            config,
+            Bystander {
+                destination: bystander.destination,
+                destination_building: bystander.destination_building,
+                focus: bystander.focus,
+            },
+            &AgentState::new(bystander.start_location, appearance),
+        );
+    });
+}
+
+fn create_bystander(
+    commands: &mut Commands,
+    config: &GameConfig,
+    bystander: Bystander,
+    state: &AgentState,
+) {
+    spawn_agent(commands, config, state)
+        .insert(bystander)
+        .insert(CollisionGroups::new(0b1, 0b1))
+        .insert(Restitution::coefficient(0.01));
+}
@@ -0,0 +1,285 @@
+//! A debugging overlay, toggled with F3 at any time: rapier's colliders, what the followers can
+//! see, where everyone is heading, the entrance sensors, and the frame rate and entity counts.
+use std::f32::consts::TAU;
+
+use bevy::{
+    diagnostic::{Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+use bevy_prototype_debug_lines::DebugLines;
+use bevy_rapier2d::prelude::{DebugRenderContext, RapierContext, RapierDebugRenderPlugin};
+
+use crate::{
+    components::{Bystander, Entrance, Follower, Hidden, Player, Walker},
+    config::GameConfig,
+    gameplay::{in_game, GameplayStage},
+    generation::get_buildings,
+    resources::RunConfig,
+    systems::follower_can_see_player,
+};
+
+pub const TOGGLE_KEY: KeyCode = KeyCode::F3;
+
+/// Drawn above every agent and building.
+const LINE_Z: f32 = 10.0;
+/// Segments in a follower's vision circle.
+const CIRCLE_SEGMENTS: usize = 48;
+/// Seconds of movement an agent's velocity and steering lines are drawn long enough for.
+const VELOCITY_SCALE: f32 = 0.5;
+
+const SEEN_COLOR: Color = Color::RED;
+const UNSEEN_COLOR: Color = Color::YELLOW;
+const SIGHT_BLOCKED_COLOR: Color = Color::GRAY;
+const DESTINATION_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.4);
+const STEERING_COLOR: Color = Color::ORANGE;
+const VELOCITY_COLOR: Color = Color::CYAN;
+const ENTRANCE_COLOR: Color = Color::BLUE;
+const PLAYER_ENTRANCE_COLOR: Color = Color::GREEN;
+/// Any entrance the player is standing in, whichever it is.
+const OCCUPIED_ENTRANCE_COLOR: Color = Color::WHITE;
+
+/// Whether the overlay is showing. Starts off.
+#[derive(Default)]
+pub struct DebugOverlay {
+    pub enabled: bool,
+}
+
+/// The frame rate and entity counts, in the bottom right corner.
+#[derive(Component)]
+pub struct DebugStatsText;
+
+/// Adds the overlay, its rapier collider rendering and the diagnostics it shows. Needs
+/// `DefaultPlugins` and `GameplayPlugin`.
+pub struct DebugOverlayPlugin;
+
+impl Plugin for DebugOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DebugOverlay>()
+            .add_plugin(RapierDebugRenderPlugin::default())
+            .add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .add_startup_system(spawn_debug_stats)
+            .add_system(toggle_debug_overlay)
+            .add_system(show_colliders.after(toggle_debug_overlay))
+            .add_system(update_debug_stats.after(toggle_debug_overlay))
+            // Drawn from where the frame's steps left everyone.
+            .add_system_set_to_stage(
+                GameplayStage::AfterSimulation,
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(draw_follower_sight)
+                    .with_system(draw_headings)
+                    .with_system(draw_entrances),
+            );
+    }
+}
+
+pub fn toggle_debug_overlay(keys: Res<Input<KeyCode>>, mut overlay: ResMut<DebugOverlay>) {
+    if keys.just_pressed(TOGGLE_KEY) {
+        overlay.enabled = !overlay.enabled;
+    }
+}
+
+/// Rapier draws the colliders itself, entrance sensors included, while the overlay is showing.
+pub fn show_colliders(overlay: Res<DebugOverlay>, mut debug_render: ResMut<DebugRenderContext>) {
+    if overlay.is_changed() {
+        debug_render.enabled = overlay.enabled;
+    }
+}
+
+pub fn spawn_debug_stats(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+                    font_size: 14.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(10.0),
+                    right: Val::Px(10.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(DebugStatsText)
+        .insert(Visibility { is_visible: false });
+}
+
+pub fn update_debug_stats(
+    overlay: Res<DebugOverlay>,
+    diagnostics: Res<Diagnostics>,
+    follower_query: Query<(), With<Follower>>,
+    bystander_query: Query<(), With<Bystander>>,
+    mut text_query: Query<(&mut Text, &mut Visibility), With<DebugStatsText>>,
+) {
+    for (mut text, mut visibility) in &mut text_query {
+        if visibility.is_visible != overlay.enabled {
+            visibility.is_visible = overlay.enabled;
+        }
+        if !overlay.enabled {
+            continue;
+        }
+        let fps = diagnostics
+            .get(FrameTimeDiagnosticsPlugin::FPS)
+            .and_then(|fps| fps.average())
+            .unwrap_or(0.0);
+        let entities = diagnostics
+            .get(EntityCountDiagnosticsPlugin::ENTITY_COUNT)
+            .and_then(|count| count.value())
+            .unwrap_or(0.0);
+        text.sections[0].value = format!(
+            "FPS {:.0}\nEntities {:.0}\nFollowers {}\nBystanders {}",
+            fps,
+            entities,
+            follower_query.iter().count(),
+            bystander_query.iter().count()
+        );
+    }
+}
+
+fn line(lines: &mut DebugLines, start: Vec2, end: Vec2, color: Color) {
+    lines.line_colored(start.extend(LINE_Z), end.extend(LINE_Z), 0.0, color);
+}
+
+fn circle(lines: &mut DebugLines, centre: Vec2, radius: f32, color: Color) {
+    let point = |index: usize| {
+        let angle = index as f32 / CIRCLE_SEGMENTS as f32 * TAU;
+        centre + Vec2::new(angle.cos(), angle.sin()) * radius
+    };
+    for index in 0..CIRCLE_SEGMENTS {
+        line(lines, point(index), point(index + 1), color);
+    }
+}
+
+fn rect(lines: &mut DebugLines, min: Vec2, max: Vec2, color: Color) {
+    let corners = [min, Vec2::new(max.x, min.y), max, Vec2::new(min.x, max.y)];
+    for (start, end) in corners.iter().zip(corners.iter().cycle().skip(1)) {
+        line(lines, *start, *end, color);
+    }
+}
+
+/// Each follower's vision range and its sight ray towards the player, red while it can see
+/// them. Followers see all the way around, so the range is a circle rather than a cone.
+pub fn draw_follower_sight(
+    overlay: Res<DebugOverlay>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    rapier_context: Res<RapierContext>,
+    mut lines: ResMut<DebugLines>,
+    player_query: Query<(Entity, &Transform, Option<&Hidden>), With<Player>>,
+    follower_query: Query<&Transform, With<Follower>>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let (player, player_transform, hidden) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let player_location = player_transform.translation.truncate();
+    let vision_range = config
+        .difficulty(run_config.difficulty)
+        .follower_vision_range;
+    for follower_transform in &follower_query {
+        let location = follower_transform.translation.truncate();
+        let seen = hidden.is_none()
+            && follower_can_see_player(
+                &rapier_context,
+                location,
+                player_location,
+                player,
+                vision_range,
+            );
+        let (range_color, ray_color) = if seen {
+            (SEEN_COLOR, SEEN_COLOR)
+        } else {
+            (UNSEEN_COLOR, SIGHT_BLOCKED_COLOR)
+        };
+        circle(&mut lines, location, vision_range, range_color);
+        // The ray only reaches as far as the follower can see.
+        let reach = (player_location - location).clamp_length_max(vision_range);
+        line(&mut lines, location, location + reach, ray_color);
+    }
+}
+
+/// Where every agent is heading: a line to each bystander's destination, and for everyone
+/// the way they're steering and the way they're actually moving. Nobody plans a route, so
+/// these are the only paths there are.
+pub fn draw_headings(
+    overlay: Res<DebugOverlay>,
+    mut lines: ResMut<DebugLines>,
+    bystander_query: Query<(&Transform, &Bystander)>,
+    walker_query: Query<(&Transform, &Walker)>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    for (transform, bystander) in &bystander_query {
+        line(
+            &mut lines,
+            transform.translation.truncate(),
+            bystander.destination.truncate(),
+            DESTINATION_COLOR,
+        );
+    }
+    for (transform, walker) in &walker_query {
+        let location = transform.translation.truncate();
+        line(
+            &mut lines,
+            location,
+            location + walker.target_velocity * VELOCITY_SCALE,
+            STEERING_COLOR,
+        );
+        line(
+            &mut lines,
+            location,
+            location + walker.velocity * VELOCITY_SCALE,
+            VELOCITY_COLOR,
+        );
+    }
+}
+
+/// Outlines every entrance sensor: green for the player's destination, white for whichever the
+/// player is standing in.
+pub fn draw_entrances(
+    overlay: Res<DebugOverlay>,
+    run_config: Res<RunConfig>,
+    rapier_context: Res<RapierContext>,
+    mut lines: ResMut<DebugLines>,
+    player_query: Query<(Entity, &Player)>,
+    entrance_query: Query<(Entity, &Entrance)>,
+) {
+    if !overlay.enabled {
+        return;
+    }
+    let (player, player_component) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    let occupied: Vec<&str> = entrance_query
+        .iter()
+        .filter(|(entrance, _)| rapier_context.intersection_pair(*entrance, player) == Some(true))
+        .map(|(_, entrance)| entrance.building_name.as_str())
+        .collect();
+    for building in get_buildings(run_config.map) {
+        let entrance = match building.entrance {
+            Some(entrance) => entrance,
+            None => continue,
+        };
+        let color = if occupied.contains(&building.name.as_str()) {
+            OCCUPIED_ENTRANCE_COLOR
+        } else if building.name == player_component.destination.name {
+            PLAYER_ENTRANCE_COLOR
+        } else {
+            ENTRANCE_COLOR
+        };
+        rect(&mut lines, entrance.min, entrance.max, color);
+    }
+}
@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    components::{Appearance, Follower},
+    config::{DifficultySettings, GameConfig},
+    gameplay::{
+        spawn_agent, AddSimulationSystems, AgentState, GameplaySystem, SimulationStage,
+        SpawnSystem, AGENT_SIDES,
+    },
+    resources::{GameRng, RunConfig},
+    save::ResumedRun,
+    sfx::Footsteps,
+    systems::{bystander_movement, follower_system, track_run_stats},
+    user_settings::Highlight,
+    AppState,
+};
+
+/// The followers: where they start, whether they can see the player, and how they give chase.
+pub struct FollowerPlugin;
+
+impl Plugin for FollowerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Game).with_system(
+                spawn_followers
+                    .label(SpawnSystem::Followers)
+                    .after(SpawnSystem::Crowd),
+            ),
+        )
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new().with_system(
+                follower_system
+                    .label(GameplaySystem::Ai)
+                    // Both draw from the run's generator, so they take turns in a fixed order.
+                    .after(bystander_movement),
+            ),
+        )
+        .add_simulation_system_set(
+            SimulationStage::AfterPhysics,
+            SystemSet::new().with_system(track_run_stats.label(GameplaySystem::Perception)),
+        );
+    }
+}
+
+fn spawn_followers(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    resumed: Option<Res<ResumedRun>>,
+) {
+    if let Some(resumed) = resumed {
+        for saved in &resumed.0.followers {
+            create_follower(&mut commands, &config, saved);
+        }
+        return;
+    }
+    let settings = config.difficulty(run_config.difficulty);
+    create_followers(&mut commands, &mut rng, &config, settings);
+}
+
+/// Where followers start, in order; extra followers on harder difficulties use the later spots.
+const FOLLOWER_SPAWNS: [(f32, f32); 4] = [(500., 500.), (1500., 550.), (1050., 950.), (300., 900.)];
+
+fn create_followers(
+    commands: &mut Commands,
+    rng: &mut GameRng,
+    config: &GameConfig,
+    settings: &DifficultySettings,
+) {
+    for index in 0..settings.follower_count {
+        let (x, y) = FOLLOWER_SPAWNS[index % FOLLOWER_SPAWNS.len()];
+        let appearance = follower_appearance(rng);
+        create_follower(
+            commands,
+            config,
+            &AgentState::new(Vec3::new(x, y, 4.), appearance),
+        );
+    }
+}
+
+fn follower_appearance(rng: &mut GameRng) -> Appearance {
+    let rng = &mut rng.0;
+    let eight_byte_range = 0.0..1.0;
+    let fill_color = Color::rgb(
+        rng.gen_range(eight_byte_range.clone()),
+        rng.gen_range(eight_byte_range.clone()),
+        rng.gen_range(eight_byte_range.clone()),
+    );
+    let stroke_color = Color::rgb(
+        rng.gen_range(eight_byte_range.clone()),
+        rng.gen_range(eight_byte_range.clone()),
+        rng.gen_range(eight_byte_range.clone()),
+    );
+    Appearance {
+        sides: AGENT_SIDES,
+        fill_color,
+        stroke_color,
+    }
+}
+
+fn create_follower(commands: &mut Commands, config: &GameConfig, state: &AgentState) {
+    spawn_agent(commands, config, state)
+        .insert(Follower)
+        .insert(Highlight::Follower)
+        .insert(Footsteps::default());
+}
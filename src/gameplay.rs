@@ -0,0 +1,408 @@
+//! The rules of a run: building the city, moving everyone, and deciding when it's won or lost.
+//! Nothing here needs a window or renderer, so runs can also be simulated headless.
+use bevy::{
+    ecs::{schedule::ShouldRun, system::EntityCommands},
+    prelude::*,
+    transform::{transform_propagate_system, TransformSystem},
+};
+use bevy_prototype_lyon::prelude::*;
+use bevy_rapier2d::prelude::{
+    Collider, GravityScale, NoUserData, PhysicsStages, RapierConfiguration, RapierPhysicsPlugin,
+    RigidBody, TimestepMode, Velocity,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Appearance, GameEntity, Walker},
+    config::GameConfig,
+    crowd::CrowdPlugin,
+    follower::FollowerPlugin,
+    input::{InputBindings, PlayerInput},
+    map::MapPlugin,
+    movement::walk,
+    player::PlayerPlugin,
+    resources::{GameRng, Prompt, RunConfig, RunStats, SpottedStingers, Stamina, Suspicion},
+    save::restore_run,
+    systems::despawn_with,
+    AppState,
+};
+
+/// The parts of a simulation step, in the order they run: input, AI and movement in
+/// `SimulationStage::Update`, then rapier's physics stages, then perception and the rules in
+/// `SimulationStage::AfterPhysics`. Nothing about a run's outcome is left to the scheduler.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameplaySystem {
+    /// Anything standing in for the player's devices, like the headless autopilot or a replay.
+    /// Real devices are read once a frame, earlier still, in `CoreStage::PreUpdate`.
+    Input,
+    /// The player, bystanders and followers choosing where they want to go.
+    Ai,
+    /// `movement::walk` taking them there.
+    Movement,
+    /// Followers looking for the player.
+    Perception,
+    /// Deciding whether the run has been won or lost.
+    Rules,
+}
+
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum GameplayStage {
+    /// A schedule of `SimulationStage`s and rapier's stages, run once for every step in
+    /// `PendingSteps`. Comes straight after `CoreStage::Update`.
+    Simulation,
+    /// Runs once a frame after all of the frame's steps, for anything showing where the run
+    /// has got to, like the camera and the HUD.
+    AfterSimulation,
+}
+
+/// The stages of one simulation step, around rapier's `SyncBackend`, `StepSimulation` and
+/// `Writeback`. Add systems to them with `AddSimulationSystems`.
+#[derive(StageLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SimulationStage {
+    Update,
+    /// Runs once rapier has stepped, so perception and the rules see where everyone ended up
+    /// and intersections that are up to date.
+    AfterPhysics,
+}
+
+/// The order the map and agents are spawned in for a new run. The agents all draw from the
+/// run's seeded generator, and rapier steps bodies in the order they were added, so the order
+/// has to be fixed for a seed to play out the same every time.
+#[derive(SystemLabel, Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SpawnSystem {
+    Map,
+    Player,
+    Crowd,
+    Followers,
+}
+
+/// Length of a simulation step in seconds. Gameplay systems move things on by this rather than
+/// the frame time, so a run plays out the same at any frame rate.
+pub const STEP: f32 = 1.0 / 60.0;
+/// The most steps a frame catches up on. Time beyond that is dropped, so one slow frame can't
+/// snowball into a run of ever slower ones.
+const MAX_STEPS_PER_FRAME: u32 = 5;
+
+/// How far the run has got in simulation steps.
+#[derive(Default)]
+pub struct SimulationClock {
+    /// Steps finished so far this run.
+    pub steps: u64,
+    /// Time the frames have moved on since the last step, not yet enough for another, in
+    /// seconds.
+    pub overstep: f32,
+}
+
+impl SimulationClock {
+    /// How far through to the next step the frame is, from 0 to 1, for drawing agents in
+    /// between steps.
+    pub fn fraction(&self) -> f32 {
+        (self.overstep / STEP).clamp(0.0, 1.0)
+    }
+}
+
+/// Steps still to be simulated this frame. Normally however many the frame time is worth, but
+/// a replay queues its own.
+#[derive(Default)]
+pub struct PendingSteps(pub u32);
+
+/// Set by the rules when they end the run. The state only changes next frame, so this stops
+/// any more steps being taken meanwhile.
+#[derive(Default)]
+pub struct RunOver(pub bool);
+
+/// Where an agent was after each of the last two steps. It's drawn in between them, so its
+/// movement looks smooth at frame rates that don't match the step rate.
+#[derive(Component, Default)]
+pub struct Interpolated {
+    previous: Option<Transform>,
+    current: Option<Transform>,
+}
+
+impl Interpolated {
+    /// Where to draw the agent `fraction` of the way through to the next step, once it's been
+    /// through a step.
+    pub fn at(&self, fraction: f32) -> Option<Transform> {
+        let current = self.current?;
+        let previous = self.previous.unwrap_or(current);
+        Some(Transform {
+            translation: previous.translation.lerp(current.translation, fraction),
+            rotation: previous.rotation.slerp(current.rotation, fraction),
+            scale: current.scale,
+        })
+    }
+}
+
+/// Everything a run needs to play out: physics, movement and the map, crowd, follower and
+/// player plugins. `GameConfig` has to be inserted before this is added, since the physics
+/// scale comes from it, and the app needs an `AppState`.
+pub struct GameplayPlugin;
+
+impl Plugin for GameplayPlugin {
+    fn build(&self, app: &mut App) {
+        let pixels_per_meter = app.world.resource::<GameConfig>().pixels_per_meter;
+        let physics_stage = |stage: PhysicsStages| {
+            SystemStage::parallel()
+                .with_system_set(RapierPhysicsPlugin::<NoUserData>::get_systems(stage))
+        };
+        let simulation = Schedule::default()
+            .with_run_criteria(IntoSystem::into_system(take_pending_step))
+            .with_stage(SimulationStage::Update, SystemStage::parallel())
+            .with_stage(
+                PhysicsStages::SyncBackend,
+                physics_stage(PhysicsStages::SyncBackend),
+            )
+            .with_stage(
+                PhysicsStages::StepSimulation,
+                physics_stage(PhysicsStages::StepSimulation),
+            )
+            .with_stage(
+                PhysicsStages::Writeback,
+                physics_stage(PhysicsStages::Writeback),
+            )
+            .with_stage(SimulationStage::AfterPhysics, SystemStage::parallel());
+
+        // Rapier's default stages run once a frame, so its systems are placed in the simulation
+        // instead. Only despawn detection stays once a frame, since runs end between steps.
+        app.add_plugin(
+            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(pixels_per_meter)
+                .with_default_system_setup(false),
+        );
+        // Rapier steps by exactly one simulation step each time, rather than reading `Time`.
+        let mut rapier_config = app.world.resource_mut::<RapierConfiguration>();
+        rapier_config.timestep_mode = TimestepMode::Fixed {
+            dt: STEP,
+            substeps: 1,
+        };
+        app.add_stage_after(CoreStage::Update, GameplayStage::Simulation, simulation)
+            .add_stage_after(
+                GameplayStage::Simulation,
+                GameplayStage::AfterSimulation,
+                SystemStage::parallel(),
+            )
+            .add_stage_before(
+                CoreStage::Last,
+                PhysicsStages::DetectDespawn,
+                physics_stage(PhysicsStages::DetectDespawn),
+            )
+            .init_resource::<RunConfig>()
+            .init_resource::<InputBindings>()
+            .init_resource::<PlayerInput>()
+            .init_resource::<SimulationClock>()
+            .init_resource::<PendingSteps>()
+            .init_resource::<RunOver>()
+            .add_plugin(MapPlugin)
+            .add_plugin(CrowdPlugin)
+            .add_plugin(FollowerPlugin)
+            .add_plugin(PlayerPlugin)
+            .add_system_set(
+                SystemSet::on_enter(AppState::Game)
+                    .with_system(start_run.exclusive_system().at_start())
+                    .with_system(restore_run.exclusive_system().at_end()),
+            )
+            .add_system_set(SystemSet::on_update(AppState::Game).with_system(queue_frame_steps))
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                interpolate_transforms.after(TransformSystem::TransformPropagate),
+            )
+            .add_simulation_system_set(
+                SimulationStage::Update,
+                SystemSet::new()
+                    .with_system(
+                        walk.label(GameplaySystem::Movement)
+                            .after(GameplaySystem::Ai),
+                    )
+                    .with_system(consume_presses.after(GameplaySystem::Ai))
+                    // Rapier picks up moved agents from their global transforms, which are
+                    // otherwise only brought up to date once a frame, in `CoreStage::PostUpdate`.
+                    .with_system(transform_propagate_system.after(GameplaySystem::Movement)),
+            )
+            .add_simulation_system_set(
+                SimulationStage::AfterPhysics,
+                SystemSet::new()
+                    .with_system(count_step.after(GameplaySystem::Rules))
+                    .with_system(track_interpolation.after(GameplaySystem::Rules)),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Game).with_system(despawn_with::<GameEntity>),
+            );
+    }
+}
+
+/// Adding systems to the stages of a simulation step, inside `GameplayStage::Simulation`.
+pub trait AddSimulationSystems {
+    fn add_simulation_system_set(
+        &mut self,
+        stage: SimulationStage,
+        system_set: SystemSet,
+    ) -> &mut Self;
+}
+
+impl AddSimulationSystems for App {
+    fn add_simulation_system_set(
+        &mut self,
+        stage: SimulationStage,
+        system_set: SystemSet,
+    ) -> &mut Self {
+        self.stage(GameplayStage::Simulation, |simulation: &mut Schedule| {
+            simulation.add_system_set_to_stage(stage, system_set)
+        })
+    }
+}
+
+/// Run criteria for the simulation: takes each pending step, and none at all outside a run or
+/// once it's over.
+fn take_pending_step(
+    state: Res<State<AppState>>,
+    run_over: Res<RunOver>,
+    mut pending: ResMut<PendingSteps>,
+) -> ShouldRun {
+    if *state.current() != AppState::Game || run_over.0 {
+        pending.0 = 0;
+        return ShouldRun::No;
+    }
+    if pending.0 == 0 {
+        return ShouldRun::No;
+    }
+    pending.0 -= 1;
+    ShouldRun::YesAndCheckAgain
+}
+
+/// Queues as many steps as the frame time makes due, carrying the remainder over.
+pub fn queue_frame_steps(
+    time: Res<Time>,
+    mut clock: ResMut<SimulationClock>,
+    mut pending: ResMut<PendingSteps>,
+) {
+    clock.overstep += time.delta_seconds();
+    let due = (clock.overstep / STEP) as u32;
+    clock.overstep -= due as f32 * STEP;
+    pending.0 += due.min(MAX_STEPS_PER_FRAME);
+}
+
+/// A press is held until a step has acted on it, and then only that step acts on it.
+fn consume_presses(mut input: ResMut<PlayerInput>) {
+    input.interact = false;
+}
+
+fn count_step(mut clock: ResMut<SimulationClock>) {
+    clock.steps += 1;
+}
+
+/// Keeps the last two places each agent has been simulated at.
+fn track_interpolation(mut agent_query: Query<(&Transform, &mut Interpolated)>) {
+    for (transform, mut interpolated) in &mut agent_query {
+        interpolated.previous = interpolated.current.or(Some(*transform));
+        interpolated.current = Some(*transform);
+    }
+}
+
+/// Draws agents between their last two steps. Only their global transforms are moved, once
+/// they've been propagated, so the simulation never sees where they're drawn.
+pub fn interpolate_transforms(
+    clock: Res<SimulationClock>,
+    mut agent_query: Query<(&Interpolated, &mut GlobalTransform)>,
+) {
+    let fraction = clock.fraction();
+    for (interpolated, mut global_transform) in &mut agent_query {
+        if let Some(transform) = interpolated.at(fraction) {
+            *global_transform = GlobalTransform::from(transform);
+        }
+    }
+}
+
+/// Run criteria for systems outside `CoreStage::Update`, like those in
+/// `GameplayStage::AfterSimulation`. The state is driven from `CoreStage::Update`, and
+/// `SystemSet::on_update` only works in a stage with a driver, so this checks the state
+/// directly instead.
+pub fn in_game(state: Res<State<AppState>>) -> ShouldRun {
+    if *state.current() == AppState::Game {
+        ShouldRun::Yes
+    } else {
+        ShouldRun::No
+    }
+}
+
+/// Seeds the run's generator and resets its counters. Exclusive, so they're in place before
+/// anything is spawned.
+fn start_run(world: &mut World) {
+    let seed = world.resource::<RunConfig>().seed;
+    world.insert_resource(GameRng::from_seed(seed));
+    world.insert_resource(RunStats::new(seed));
+    world.insert_resource(SpottedStingers::default());
+    world.insert_resource(Suspicion::default());
+    world.insert_resource(Stamina::default());
+    world.insert_resource(Prompt::default());
+    world.insert_resource(SimulationClock::default());
+    world.insert_resource(RunOver::default());
+}
+
+/// The player and followers are hexagons.
+pub const AGENT_SIDES: usize = 6;
+
+/// Where an agent is, how it's moving and how it looks: everything needed to spawn it, whether
+/// it's new or being put back from a saved run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentState {
+    pub translation: Vec3,
+    pub linvel: Vec2,
+    pub angvel: f32,
+    pub walker: Walker,
+    pub appearance: Appearance,
+}
+
+impl AgentState {
+    /// A new agent at `translation`, with the slight drift every agent has always started with.
+    pub fn new(translation: Vec3, appearance: Appearance) -> Self {
+        AgentState {
+            translation,
+            linvel: Vec2::new(1.0, 2.0),
+            angvel: 0.2,
+            walker: Walker::default(),
+            appearance,
+        }
+    }
+
+    pub fn capture(
+        transform: &Transform,
+        velocity: &Velocity,
+        walker: &Walker,
+        appearance: &Appearance,
+    ) -> Self {
+        AgentState {
+            translation: transform.translation,
+            linvel: velocity.linvel,
+            angvel: velocity.angvel,
+            walker: *walker,
+            appearance: *appearance,
+        }
+    }
+}
+
+/// Spawns what every agent has: its shape, built from its appearance, a rapier body and
+/// collider, and its movement. The caller adds whatever makes it the player, a follower or a
+/// bystander.
+pub fn spawn_agent<'w, 's, 'a>(
+    commands: &'a mut Commands<'w, 's>,
+    config: &GameConfig,
+    state: &AgentState,
+) -> EntityCommands<'w, 's, 'a> {
+    let mut agent = commands.spawn_bundle(GeometryBuilder::build_as(
+        &state.appearance.shape(config.agent_size),
+        state.appearance.draw_mode(),
+        Transform::from_translation(state.translation),
+    ));
+    agent
+        .insert(RigidBody::Dynamic)
+        .insert(GravityScale(0.0))
+        .insert(Collider::cuboid(config.agent_size, config.agent_size))
+        .insert(state.walker)
+        .insert(Interpolated::default())
+        .insert(state.appearance)
+        .insert(GameEntity)
+        .insert(Velocity {
+            linvel: state.linvel,
+            angvel: state.angvel,
+        });
+    agent
+}
@@ -2,17 +2,19 @@ use bevy::{prelude::*, sprite::Rect};
 use bevy_rapier2d::prelude::*;
 use rand::{seq::SliceRandom, Rng};
 
-use crate::components::Entrance;
+use crate::{
+    components::{Entrance, GameEntity},
+    config::GameConfig,
+};
 
 pub struct PlayerInit {
     pub origin: Building,
     pub destination: Building,
 }
 
-pub fn player_init() -> PlayerInit {
-    let mut rng = rand::thread_rng();
-    let binding = get_buildings();
-    let places: Vec<&Building> = binding.choose_multiple(&mut rng, 2).collect();
+pub fn player_init(rng: &mut impl Rng, map: usize) -> PlayerInit {
+    let binding = get_buildings(map);
+    let places: Vec<&Building> = binding.choose_multiple(rng, 2).collect();
     PlayerInit {
         origin: places[0].clone(),
         destination: places[1].clone(),
@@ -27,8 +29,7 @@ pub struct BystanderInit {
     pub destination: Vec3,
     pub destination_building: Building,
 }
-pub fn generate_bystander() -> BystanderInit {
-    let mut rng = rand::thread_rng();
+pub fn generate_bystander(rng: &mut impl Rng, config: &GameConfig, map: usize) -> BystanderInit {
     let eight_byte_range = 0.0..1.0;
     let fill_color = Color::rgb(
         rng.gen_range(eight_byte_range.clone()),
@@ -45,8 +46,8 @@ pub fn generate_bystander() -> BystanderInit {
     let y = rng.gen_range(10.0..1070.);
     let z = rng.gen_range(0.0..1.0);
 
-    let binding = get_buildings();
-    let destination_building = binding.choose(&mut rng).unwrap().clone();
+    let binding = get_buildings(map);
+    let destination_building = binding.choose(rng).unwrap().clone();
     // TODO make the entry to the building
     let entrance = destination_building.entrance.unwrap();
     let destination = Vec3::new(
@@ -59,7 +60,7 @@ pub fn generate_bystander() -> BystanderInit {
         stroke_color,
         side_count,
         start_location: Vec3::new(x, y, z),
-        focus: rng.gen_range(1.0..4.),
+        focus: rng.gen_range(config.bystander_focus_min..config.bystander_focus_max),
         destination,
         destination_building,
     }
@@ -76,6 +77,7 @@ impl Building {
     pub fn add_to_scene(&self, commands: &mut Commands) {
         commands
             .spawn()
+            .insert(GameEntity)
             .insert(RigidBody::Fixed)
             .insert(Collider::cuboid(
                 self.bounds.width() / 2.,
@@ -88,12 +90,14 @@ impl Building {
             )));
         commands
             .spawn()
+            .insert(GameEntity)
             .insert(RigidBody::Fixed)
             .insert(Collider::cuboid(
                 self.entrance.unwrap().width() / 2.,
                 self.entrance.unwrap().height() / 2.,
             ))
             .insert(Sensor)
+            .insert(ActiveEvents::COLLISION_EVENTS)
             .insert(Entrance {
                 building_name: self.name.clone(),
             })
@@ -105,7 +109,43 @@ impl Building {
     }
 }
 
-pub fn get_buildings() -> Vec<Building> {
+/// The building on map `map` called `name`, if there is one.
+pub fn building_named(map: usize, name: &str) -> Option<Building> {
+    get_buildings(map)
+        .into_iter()
+        .find(|building| building.name == name)
+}
+
+/// Size of every map in pixels; the map's bottom-left corner is at the origin.
+pub const MAP_WIDTH: f32 = 1920.0;
+pub const MAP_HEIGHT: f32 = 1080.0;
+
+/// A map a run can be played on.
+pub struct MapInfo {
+    pub name: &'static str,
+    /// The artwork drawn behind the map, under `assets/`.
+    pub texture: &'static str,
+    buildings: fn() -> Vec<Building>,
+}
+
+/// Every map that can be picked from the menu. `RunConfig::map` indexes into this.
+pub static MAPS: [MapInfo; 1] = [MapInfo {
+    name: "Downtown",
+    texture: "map.png",
+    buildings: downtown_buildings,
+}];
+
+/// Map `index`, or the first map if there's no such map.
+pub fn map_info(index: usize) -> &'static MapInfo {
+    MAPS.get(index).unwrap_or(&MAPS[0])
+}
+
+/// The buildings on map `index`.
+pub fn get_buildings(index: usize) -> Vec<Building> {
+    (map_info(index).buildings)()
+}
+
+fn downtown_buildings() -> Vec<Building> {
     vec![
         // Building {
         //     name: "Bus".to_string(),
@@ -162,7 +202,7 @@ pub fn get_buildings() -> Vec<Building> {
         Building {
             name: "Strip Mall".to_string(),
             bounds: Rect {
-                min: Vec2::new(1689., 1080. - 1080.),
+                min: Vec2::new(1689., 0.),
                 max: Vec2::new(1920., 1080.0 - 649.0),
             },
             entrance: Some(Rect {
@@ -0,0 +1,261 @@
+//! Plays runs without a window, with a scripted player standing in for a person, as fast as
+//! the machine allows. Start it with `--headless`; `--runs <n>`, `--seed <first seed>` and
+//! `--difficulty <easy|normal|hard|custom>` pick what gets played.
+use bevy::{hierarchy::HierarchyPlugin, prelude::*, time::TimePlugin, transform::TransformPlugin};
+
+use crate::{
+    components::{Entrance, Follower, Player},
+    config::{Difficulty, GameConfig},
+    gameplay::{
+        AddSimulationSystems, GameplayPlugin, GameplaySystem, PendingSteps, SimulationStage, STEP,
+    },
+    input::PlayerInput,
+    resources::{RunConfig, RunStats, Stamina, Suspicion},
+    AppState,
+};
+
+/// How long the scripted player has to make no headway before it tries to go around whatever
+/// it's stuck on, and how long it spends going around.
+const STUCK_TIME: f32 = 1.0;
+const DETOUR_TIME: f32 = 1.5;
+
+pub struct HeadlessOptions {
+    pub runs: u64,
+    pub first_seed: u64,
+    pub difficulty: Difficulty,
+}
+
+impl HeadlessOptions {
+    /// `None` unless `--headless` was passed.
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip(1);
+        let mut headless = false;
+        let mut options = HeadlessOptions {
+            runs: 100,
+            first_seed: 0,
+            difficulty: Difficulty::default(),
+        };
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--headless" => headless = true,
+                "--runs" => {
+                    if let Some(runs) = args.next().and_then(|value| value.parse().ok()) {
+                        options.runs = runs;
+                    }
+                }
+                "--seed" => {
+                    if let Some(seed) = args.next().and_then(|value| value.parse().ok()) {
+                        options.first_seed = seed;
+                    }
+                }
+                "--difficulty" => {
+                    let name = args.next().unwrap_or_default();
+                    match Difficulty::ALL
+                        .into_iter()
+                        .find(|difficulty| difficulty.name().eq_ignore_ascii_case(&name))
+                    {
+                        Some(difficulty) => options.difficulty = difficulty,
+                        None => eprintln!("Unknown difficulty {:?}, using Normal", name),
+                    }
+                }
+                _ => {}
+            }
+        }
+        headless.then_some(options)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Outcome {
+    Won,
+    /// Reached the door with a follower watching.
+    Spotted,
+    TooSuspicious,
+    OutOfTime,
+}
+
+impl Outcome {
+    const ALL: [Outcome; 4] = [
+        Outcome::Won,
+        Outcome::Spotted,
+        Outcome::TooSuspicious,
+        Outcome::OutOfTime,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Spotted => "spotted at the door",
+            Outcome::TooSuspicious => "too suspicious",
+            Outcome::OutOfTime => "out of time",
+        }
+    }
+}
+
+/// Plays every requested seed in turn, printing each outcome and then a summary.
+pub fn run(config: GameConfig, options: HeadlessOptions) {
+    let time_limit = config.difficulty(options.difficulty).time_limit;
+    let mut outcomes = Vec::new();
+    for seed in options.first_seed..options.first_seed + options.runs {
+        let (outcome, stats) = simulate(&config, seed, options.difficulty);
+        println!(
+            "seed {}: {} after {:.1}s, spotted {} times, closest follower {:.0}px, score {}",
+            seed,
+            outcome.name(),
+            stats.time_taken,
+            stats.times_spotted,
+            stats.closest_approach,
+            stats.score(),
+        );
+        outcomes.push((outcome, stats));
+    }
+
+    println!(
+        "\n{} runs on {} with a {:.0}s limit",
+        outcomes.len(),
+        options.difficulty.name(),
+        time_limit
+    );
+    for outcome in Outcome::ALL {
+        let count = outcomes.iter().filter(|(o, _)| *o == outcome).count();
+        let percent = count as f32 / outcomes.len().max(1) as f32 * 100.0;
+        println!("  {:<20} {:>6} ({:.1}%)", outcome.name(), count, percent);
+    }
+    let wins: Vec<f32> = outcomes
+        .iter()
+        .filter(|(outcome, _)| *outcome == Outcome::Won)
+        .map(|(_, stats)| stats.time_taken)
+        .collect();
+    if !wins.is_empty() {
+        println!(
+            "  average winning time {:.1}s",
+            wins.iter().sum::<f32>() / wins.len() as f32
+        );
+    }
+}
+
+/// An app that plays a run without a window, one simulation step per update, starting in
+/// `AppState::Game`. Nothing drives the player; add a `GameplaySystem::Input` system to the
+/// simulation that writes `PlayerInput`, or move them directly.
+pub fn headless_app(config: GameConfig, run_config: RunConfig) -> App {
+    let mut app = App::new();
+    // Time stands still, so no steps come due from it; `take_one_step` queues them instead, so
+    // runs aren't held to real time.
+    app.add_plugins_with(MinimalPlugins, |group| group.disable::<TimePlugin>())
+        .add_plugin(TransformPlugin)
+        .add_plugin(HierarchyPlugin)
+        .init_resource::<Time>()
+        .add_system_to_stage(CoreStage::First, take_one_step)
+        .insert_resource(config)
+        .insert_resource(run_config)
+        .add_state(AppState::Game)
+        .add_plugin(GameplayPlugin);
+    app
+}
+
+/// Plays one run to the end and reports how it went.
+fn simulate(config: &GameConfig, seed: u64, difficulty: Difficulty) -> (Outcome, RunStats) {
+    let mut app = headless_app(
+        config.clone(),
+        RunConfig {
+            seed,
+            difficulty,
+            ..default()
+        },
+    );
+    app.add_simulation_system_set(
+        SimulationStage::Update,
+        SystemSet::new().with_system(
+            autopilot
+                .label(GameplaySystem::Input)
+                .before(GameplaySystem::Ai),
+        ),
+    );
+
+    while *app.world.resource::<State<AppState>>().current() == AppState::Game {
+        app.update();
+    }
+
+    let won = *app.world.resource::<State<AppState>>().current() == AppState::Win;
+    let settings = config.difficulty(difficulty);
+    let stats = app.world.remove_resource::<RunStats>().unwrap();
+    let outcome = if won {
+        Outcome::Won
+    } else if stats.time_taken >= settings.time_limit {
+        Outcome::OutOfTime
+    } else if app.world.resource::<Suspicion>().0 >= 1.0 {
+        Outcome::TooSuspicious
+    } else {
+        Outcome::Spotted
+    };
+    (outcome, stats)
+}
+
+fn take_one_step(mut pending: ResMut<PendingSteps>) {
+    pending.0 += 1;
+}
+
+/// What the scripted player remembers between updates.
+#[derive(Default)]
+struct Autopilot {
+    last_location: Vec2,
+    stuck_for: f32,
+    detour: Vec2,
+    detour_for: f32,
+    /// Detours alternate sides, so one that didn't work isn't just repeated.
+    detours_taken: u32,
+}
+
+/// A deliberately simple player: heads straight for the destination door, sprinting while a
+/// follower is within earshot, and sidesteps for a moment when a building stops it.
+fn autopilot(
+    config: Res<GameConfig>,
+    stamina: Res<Stamina>,
+    mut input: ResMut<PlayerInput>,
+    mut pilot: Local<Autopilot>,
+    player_query: Query<(&Player, &Transform)>,
+    entrance_query: Query<(&Entrance, &Transform)>,
+    follower_query: Query<&Transform, With<Follower>>,
+) {
+    let (player, transform) = player_query.single();
+    let location = transform.translation.truncate();
+    let destination = entrance_query
+        .iter()
+        .find(|(entrance, _)| entrance.building_name == player.destination.name)
+        .map(|(_, transform)| transform.translation.truncate())
+        .unwrap_or(location);
+    let heading = (destination - location).normalize_or_zero();
+
+    if pilot.detour_for > 0.0 {
+        pilot.detour_for -= STEP;
+    } else if location.distance(pilot.last_location) < config.player_speed * STEP * 0.25 {
+        pilot.stuck_for += STEP;
+        if pilot.stuck_for >= STUCK_TIME {
+            let side = if pilot.detours_taken.is_multiple_of(2) {
+                1.0
+            } else {
+                -1.0
+            };
+            pilot.detour = (heading.perp() * side - heading * 0.5).normalize_or_zero();
+            pilot.detour_for = DETOUR_TIME;
+            pilot.detours_taken += 1;
+            pilot.stuck_for = 0.0;
+        }
+    } else {
+        pilot.stuck_for = 0.0;
+    }
+    pilot.last_location = location;
+
+    let chased = follower_query.iter().any(|follower| {
+        follower.translation.truncate().distance(location) <= config.follower_hearing_range
+    });
+    *input = PlayerInput {
+        movement: if pilot.detour_for > 0.0 {
+            pilot.detour
+        } else {
+            heading
+        },
+        sprint: chased && stamina.0 > 0.0,
+        ..default()
+    };
+}
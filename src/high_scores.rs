@@ -0,0 +1,267 @@
+use std::{
+    cmp::Reverse,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::Difficulty,
+    generation::map_info,
+    menu::take_press,
+    replay::Playback,
+    resources::{RunConfig, RunStats, SelectedMap},
+    storage::{load_ron, save_path, save_ron},
+    AppState,
+};
+
+const HIGH_SCORES_FILE: &str = "high_scores.ron";
+/// Bump this when the layout changes, and teach `HighScores::migrate` about the old one.
+/// Version 2 keeps only wins in `best`; version 1 builds also put losses there.
+const HIGH_SCORES_VERSION: u32 = 2;
+const BEST_RUNS_PER_MAP: usize = 10;
+const HISTORY_LENGTH: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunRecord {
+    pub map: String,
+    /// Runs saved before difficulties existed were all played on what is now Normal.
+    #[serde(default)]
+    pub difficulty: Difficulty,
+    pub seed: u64,
+    pub time: f32,
+    pub score: u32,
+    pub won: bool,
+    /// Seconds since the unix epoch when the run finished.
+    pub finished_at: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HighScores {
+    pub version: u32,
+    #[serde(default)]
+    pub best: Vec<RunRecord>,
+    #[serde(default)]
+    pub history: Vec<RunRecord>,
+}
+
+impl Default for HighScores {
+    fn default() -> Self {
+        HighScores {
+            version: HIGH_SCORES_VERSION,
+            best: Vec::new(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl HighScores {
+    pub fn load() -> Self {
+        load_ron::<HighScores>(&save_path(HIGH_SCORES_FILE))
+            .map(HighScores::migrate)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        if self.version > HIGH_SCORES_VERSION {
+            // Written by a newer build; leave it alone rather than dropping fields we don't know.
+            warn!("High scores were saved by a newer version, not overwriting them");
+            return;
+        }
+        save_ron(&save_path(HIGH_SCORES_FILE), self);
+    }
+
+    /// Brings scores saved by an older build up to the current version. Files from newer builds
+    /// are left as they are, and `save` won't overwrite them.
+    pub fn migrate(mut self) -> Self {
+        if self.version < 2 {
+            // Losses were wrongly ranked alongside wins; they're still in the history.
+            self.best.retain(|record| record.won);
+            self.version = 2;
+        }
+        self
+    }
+
+    pub fn record(&mut self, run: RunRecord) {
+        self.history.push(run.clone());
+        if self.history.len() > HISTORY_LENGTH {
+            let excess = self.history.len() - HISTORY_LENGTH;
+            self.history.drain(..excess);
+        }
+
+        // Only wins are ranked; losses are just history.
+        if !run.won {
+            return;
+        }
+        self.best.push(run.clone());
+        self.best.sort_by_key(|record| Reverse(record.score));
+        let mut kept = 0;
+        self.best.retain(|record| {
+            if record.map != run.map || record.difficulty != run.difficulty {
+                return true;
+            }
+            kept += 1;
+            kept <= BEST_RUNS_PER_MAP
+        });
+    }
+
+    pub fn best_for<'a>(
+        &'a self,
+        map: &'a str,
+        difficulty: Difficulty,
+    ) -> impl Iterator<Item = &'a RunRecord> {
+        self.best
+            .iter()
+            .filter(move |record| record.map == map && record.difficulty == difficulty)
+    }
+}
+
+pub fn load_high_scores(mut commands: Commands) {
+    commands.insert_resource(HighScores::load());
+}
+
+pub fn record_win(
+    high_scores: Option<ResMut<HighScores>>,
+    playback: Option<Res<Playback>>,
+    stats: Res<RunStats>,
+    run_config: Res<RunConfig>,
+) {
+    record_run(
+        high_scores,
+        playback,
+        &stats,
+        map_info(run_config.map).name,
+        run_config.difficulty,
+        true,
+    );
+}
+
+pub fn record_loss(
+    high_scores: Option<ResMut<HighScores>>,
+    playback: Option<Res<Playback>>,
+    stats: Res<RunStats>,
+    run_config: Res<RunConfig>,
+) {
+    record_run(
+        high_scores,
+        playback,
+        &stats,
+        map_info(run_config.map).name,
+        run_config.difficulty,
+        false,
+    );
+}
+
+fn record_run(
+    high_scores: Option<ResMut<HighScores>>,
+    playback: Option<Res<Playback>>,
+    stats: &RunStats,
+    map: &str,
+    difficulty: Difficulty,
+    won: bool,
+) {
+    // Watching a replay isn't playing. A replay started with `--replay` also skips the menu,
+    // which is where the scores are loaded.
+    let mut high_scores = match high_scores {
+        Some(high_scores) if playback.is_none() => high_scores,
+        _ => return,
+    };
+    let finished_at = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or(0);
+    high_scores.record(RunRecord {
+        map: map.to_string(),
+        difficulty,
+        seed: stats.seed,
+        time: stats.time_taken,
+        score: stats.score(),
+        won,
+        finished_at,
+    });
+    high_scores.save();
+}
+
+/// Anything spawned for the high score screen, removed again when it's left.
+#[derive(Component)]
+pub struct HighScoresEntity;
+
+pub fn high_scores_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    high_scores: Res<HighScores>,
+    map: Res<SelectedMap>,
+    difficulty: Res<Difficulty>,
+) {
+    let map_name = map_info(map.0).name;
+    let font = asset_server.load("fonts/Akira Expanded Demo.otf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+    let row_style = TextStyle {
+        font,
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    let mut rows: Vec<String> = high_scores
+        .best_for(map_name, *difficulty)
+        .enumerate()
+        .map(|(rank, record)| {
+            format!(
+                "{}. {}  {:.1}s  seed {}",
+                rank + 1,
+                record.score,
+                record.time,
+                record.seed
+            )
+        })
+        .collect();
+    if rows.is_empty() {
+        rows.push("No runs yet".to_string());
+    }
+    rows.push("Press Esc to go back".to_string());
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                // UI flows bottom-up, so reverse the column to read top to bottom.
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(HighScoresEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                format!("{} {} High Scores", map_name, difficulty.name()),
+                title_style,
+            ));
+            for row in rows {
+                parent.spawn_bundle(TextBundle::from_section(row, row_style.clone()));
+            }
+        });
+}
+
+pub fn high_scores_back_to_menu(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    if take_press(
+        &mut keys,
+        &gamepads,
+        &mut gamepad_buttons,
+        KeyCode::Escape,
+        GamepadButtonType::East,
+    ) {
+        app_state.set(AppState::Menu).unwrap();
+    }
+}
@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::GameEntity,
+    config::GameConfig,
+    resources::{Errands, Prompt, RunConfig, RunStats, Stamina, Suspicion},
+};
+
+const BAR_WIDTH: f32 = 200.0;
+const BAR_HEIGHT: f32 = 12.0;
+const PANEL_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const BAR_BACKGROUND_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.2);
+const SUSPICION_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+const STAMINA_COLOR: Color = Color::rgb(0.3, 0.8, 0.4);
+const DONE_COLOR: Color = Color::rgb(0.5, 0.5, 0.5);
+
+#[derive(Component)]
+pub struct HudObjective;
+
+#[derive(Component)]
+pub struct HudTimer;
+
+/// The filled part of the suspicion meter.
+#[derive(Component)]
+pub struct HudSuspicion;
+
+/// The filled part of the stamina bar.
+#[derive(Component)]
+pub struct HudStamina;
+
+#[derive(Component)]
+pub struct HudPrompt;
+
+fn bar(
+    parent: &mut ChildBuilder,
+    label: &str,
+    style: &TextStyle,
+    color: Color,
+    marker: impl Component,
+) {
+    parent.spawn_bundle(TextBundle::from_section(label, style.clone()));
+    parent
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Px(BAR_WIDTH), Val::Px(BAR_HEIGHT)),
+                margin: UiRect {
+                    bottom: Val::Px(6.0),
+                    ..default()
+                },
+                ..default()
+            },
+            color: BAR_BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .with_children(|background| {
+            background
+                .spawn_bundle(NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.0), Val::Percent(100.0)),
+                        ..default()
+                    },
+                    color: color.into(),
+                    ..default()
+                })
+                .insert(marker);
+        });
+}
+
+/// Lays out the in-game HUD with empty values; the update systems fill them in as soon as the
+/// run's resources are in place.
+pub fn spawn_hud(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+        font_size: 18.0,
+        color: Color::WHITE,
+    };
+    let panel = |position: UiRect<Val>| NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position,
+            // UI flows bottom-up, so reverse the column to read top to bottom.
+            flex_direction: FlexDirection::ColumnReverse,
+            padding: UiRect::all(Val::Px(8.0)),
+            ..default()
+        },
+        color: PANEL_COLOR.into(),
+        ..default()
+    };
+
+    commands
+        .spawn_bundle(panel(UiRect {
+            top: Val::Px(10.0),
+            right: Val::Px(10.0),
+            ..default()
+        }))
+        .insert(GameEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Don't Let Them Follow You!",
+                style.clone(),
+            ));
+            parent
+                .spawn_bundle(TextBundle::from_section("", style.clone()))
+                .insert(HudObjective);
+        });
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(GameEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 28.0,
+                        ..style.clone()
+                    },
+                ))
+                .insert(HudTimer);
+        });
+
+    commands
+        .spawn_bundle(panel(UiRect {
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        }))
+        .insert(GameEntity)
+        .with_children(|parent| {
+            bar(parent, "Suspicion", &style, SUSPICION_COLOR, HudSuspicion);
+            bar(parent, "Stamina", &style, STAMINA_COLOR, HudStamina);
+        });
+
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(40.0),
+                    ..default()
+                },
+                size: Size::new(Val::Percent(100.0), Val::Auto),
+                justify_content: JustifyContent::Center,
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(GameEntity)
+        .with_children(|parent| {
+            parent
+                .spawn_bundle(TextBundle::from_section("", style))
+                .insert(HudPrompt);
+        });
+}
+
+pub fn update_hud_objective(
+    errands: Res<Errands>,
+    mut text_query: Query<&mut Text, With<HudObjective>>,
+) {
+    if !errands.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        let template = text.sections[0].style.clone();
+        text.sections = errands
+            .0
+            .iter()
+            .enumerate()
+            .map(|(index, errand)| {
+                let separator = if index == 0 { "" } else { "\n" };
+                let (value, color) = if errand.done {
+                    (
+                        format!("{}{} - Done", separator, errand.building),
+                        DONE_COLOR,
+                    )
+                } else {
+                    (
+                        format!("{}Get To {}", separator, errand.building),
+                        Color::WHITE,
+                    )
+                };
+                TextSection {
+                    value,
+                    style: TextStyle {
+                        color,
+                        ..template.clone()
+                    },
+                }
+            })
+            .collect();
+        if text.sections.is_empty() {
+            text.sections.push(TextSection {
+                value: String::new(),
+                style: template,
+            });
+        }
+    }
+}
+
+/// Counts down to the time limit. Run stats change every frame, so this only rewrites the text
+/// when the displayed second does.
+pub fn update_hud_timer(
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    stats: Res<RunStats>,
+    mut text_query: Query<&mut Text, With<HudTimer>>,
+) {
+    if !stats.is_changed() {
+        return;
+    }
+    let time_limit = config.difficulty(run_config.difficulty).time_limit;
+    let remaining = (time_limit - stats.time_taken).max(0.0).ceil() as u32;
+    let value = format!("{}:{:02}", remaining / 60, remaining % 60);
+    for mut text in &mut text_query {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
+
+pub fn update_hud_suspicion(
+    suspicion: Res<Suspicion>,
+    mut bar_query: Query<&mut Style, With<HudSuspicion>>,
+) {
+    if !suspicion.is_changed() {
+        return;
+    }
+    for mut style in &mut bar_query {
+        style.size.width = Val::Percent(suspicion.0 * 100.0);
+    }
+}
+
+pub fn update_hud_stamina(
+    stamina: Res<Stamina>,
+    mut bar_query: Query<&mut Style, With<HudStamina>>,
+) {
+    if !stamina.is_changed() {
+        return;
+    }
+    for mut style in &mut bar_query {
+        style.size.width = Val::Percent(stamina.0 * 100.0);
+    }
+}
+
+pub fn update_hud_prompt(prompt: Res<Prompt>, mut text_query: Query<&mut Text, With<HudPrompt>>) {
+    if !prompt.is_changed() {
+        return;
+    }
+    for mut text in &mut text_query {
+        text.sections[0].value = prompt.0.clone().unwrap_or_default();
+    }
+}
@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    camera::viewport_to_world,
+    components::{Entrance, Follower, GameEntity, MainCamera, Player},
+    config::GameConfig,
+    user_settings::Highlight,
+};
+
+/// How far in from the screen edge the arrows sit, in logical pixels.
+const EDGE_MARGIN: f32 = 40.0;
+/// Gap between an arrow and its distance label, in logical pixels.
+const LABEL_OFFSET: f32 = 28.0;
+/// Above every agent and building.
+const INDICATOR_Z: f32 = 50.0;
+
+const DESTINATION_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+const FOLLOWER_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+/// An arrow at the screen edge pointing at something off-screen.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum Indicator {
+    Destination,
+    /// The nearest follower, while it's close enough to hear but off-screen.
+    Follower,
+}
+
+/// Shows how far away the destination is, next to its arrow.
+#[derive(Component)]
+pub struct IndicatorLabel;
+
+pub fn spawn_indicators(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let arrow = shapes::Polygon {
+        points: vec![
+            Vec2::new(14.0, 0.0),
+            Vec2::new(-8.0, 10.0),
+            Vec2::new(-8.0, -10.0),
+        ],
+        closed: true,
+    };
+    for (indicator, color) in [
+        (Indicator::Destination, DESTINATION_COLOR),
+        (Indicator::Follower, FOLLOWER_COLOR),
+    ] {
+        let arrow_entity = commands
+            .spawn_bundle(GeometryBuilder::build_as(
+                &arrow,
+                DrawMode::Outlined {
+                    fill_mode: FillMode::color(color),
+                    outline_mode: StrokeMode::new(Color::BLACK, 2.0),
+                },
+                Transform::from_xyz(0.0, 0.0, INDICATOR_Z),
+            ))
+            .insert(Visibility { is_visible: false })
+            .insert(indicator)
+            .insert(GameEntity)
+            .id();
+        if indicator == Indicator::Follower {
+            commands.entity(arrow_entity).insert(Highlight::Follower);
+        }
+    }
+    commands
+        .spawn_bundle(Text2dBundle {
+            text: Text::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+                    font_size: 16.0,
+                    color: DESTINATION_COLOR,
+                },
+            )
+            .with_alignment(TextAlignment::CENTER),
+            visibility: Visibility { is_visible: false },
+            ..default()
+        })
+        .insert(IndicatorLabel)
+        .insert(GameEntity);
+}
+
+/// Where on the inset screen edge an arrow towards `target` should sit, and which way it
+/// points, or `None` while `target` is on screen.
+fn edge_position(viewport_target: Vec2, viewport_size: Vec2) -> Option<(Vec2, Vec2)> {
+    let on_screen =
+        viewport_target.cmpge(Vec2::ZERO).all() && viewport_target.cmple(viewport_size).all();
+    if on_screen {
+        return None;
+    }
+    let center = viewport_size / 2.0;
+    let direction = (viewport_target - center).normalize_or_zero();
+    let inset = (center - Vec2::splat(EDGE_MARGIN)).max(Vec2::ZERO);
+    let reach = (inset.x / direction.x.abs()).min(inset.y / direction.y.abs());
+    Some((center + direction * reach, direction))
+}
+
+#[allow(clippy::type_complexity)]
+pub fn update_indicators(
+    config: Res<GameConfig>,
+    camera_query: Query<(&Camera, &Transform, &OrthographicProjection), With<MainCamera>>,
+    player_query: Query<(&Player, &Transform)>,
+    entrance_query: Query<(&Entrance, &Transform)>,
+    follower_query: Query<&Transform, With<Follower>>,
+    mut arrow_query: Query<
+        (&Indicator, &mut Transform, &mut Visibility),
+        (
+            Without<MainCamera>,
+            Without<Player>,
+            Without<Entrance>,
+            Without<Follower>,
+        ),
+    >,
+    mut label_query: Query<
+        (&mut Text, &mut Transform, &mut Visibility),
+        (
+            With<IndicatorLabel>,
+            Without<Indicator>,
+            Without<MainCamera>,
+            Without<Player>,
+            Without<Entrance>,
+            Without<Follower>,
+        ),
+    >,
+) {
+    let (camera, camera_transform, projection) = camera_query.single();
+    // The camera has moved this frame but its global transform isn't updated until later, so
+    // work from its local one (it has no parent) to keep the arrows from lagging behind.
+    let camera_transform = GlobalTransform::from(*camera_transform);
+    let viewport_size = match camera.logical_viewport_size() {
+        Some(size) => size,
+        None => return,
+    };
+    let (player, player_transform) = player_query.single();
+    let player_location = player_transform.translation.truncate();
+
+    let destination = entrance_query
+        .iter()
+        .find(|(entrance, _)| entrance.building_name == player.destination.name)
+        .map(|(_, transform)| transform.translation.truncate());
+    let heard_follower = follower_query
+        .iter()
+        .map(|transform| transform.translation.truncate())
+        .filter(|location| location.distance(player_location) <= config.follower_hearing_range)
+        .min_by(|a, b| {
+            a.distance(player_location)
+                .total_cmp(&b.distance(player_location))
+        });
+
+    let (mut label, mut label_transform, mut label_visibility) = label_query.single_mut();
+    label_visibility.is_visible = false;
+    for (indicator, mut transform, mut visibility) in &mut arrow_query {
+        let target = match indicator {
+            Indicator::Destination => destination,
+            Indicator::Follower => heard_follower,
+        };
+        let placement = target.and_then(|target| {
+            let viewport_target =
+                camera.world_to_viewport(&camera_transform, target.extend(0.0))?;
+            let (edge, direction) = edge_position(viewport_target, viewport_size)?;
+            let world = viewport_to_world(camera, &camera_transform, edge)?;
+            Some((target, world, direction))
+        });
+        let (target, world, direction) = match placement {
+            Some(placement) => placement,
+            None => {
+                visibility.is_visible = false;
+                continue;
+            }
+        };
+        visibility.is_visible = true;
+        // Keep the arrow the same size on screen however far the camera is zoomed.
+        *transform = Transform::from_translation(world.extend(INDICATOR_Z))
+            .with_rotation(Quat::from_rotation_z(direction.y.atan2(direction.x)))
+            .with_scale(Vec3::splat(projection.scale));
+
+        if *indicator == Indicator::Destination {
+            let label_location = world - direction * LABEL_OFFSET * projection.scale;
+            *label_transform = Transform::from_translation(label_location.extend(INDICATOR_Z))
+                .with_scale(Vec3::splat(projection.scale));
+            let distance = format!(
+                "{:.0}m",
+                target.distance(player_location) / config.pixels_per_meter
+            );
+            // Only touch the text when the whole metre changes, so it isn't re-laid out every
+            // frame.
+            if label.sections[0].value != distance {
+                label.sections[0].value = distance;
+            }
+            label_visibility.is_visible = true;
+        }
+    }
+}
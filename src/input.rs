@@ -0,0 +1,286 @@
+use bevy::{
+    input::mouse::{MouseScrollUnit, MouseWheel},
+    prelude::*,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::storage::{load_ron, save_path, save_ron};
+
+pub const BINDINGS_FILE: &str = "controls.ron";
+
+/// Sticks never rest exactly at zero; anything closer to the centre than this is ignored.
+const STICK_DEADZONE: f32 = 0.15;
+
+/// Wheel notches per second that holding the right stick fully up or down is worth.
+const STICK_ZOOM_RATE: f32 = 5.0;
+
+/// Pixel-precise touchpads report scrolling in pixels; this many make up one wheel notch.
+const PIXELS_PER_NOTCH: f32 = 50.0;
+
+/// Everything the player can do in a run, independent of the device doing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Action {
+    MoveUp,
+    MoveDown,
+    MoveLeft,
+    MoveRight,
+    Sprint,
+    Sneak,
+    Interact,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 8] = [
+        Action::MoveUp,
+        Action::MoveDown,
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Sprint,
+        Action::Sneak,
+        Action::Interact,
+        Action::Pause,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Action::MoveUp => "Move Up",
+            Action::MoveDown => "Move Down",
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Sprint => "Sprint",
+            Action::Sneak => "Sneak",
+            Action::Interact => "Interact",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// Any of the keys, or the button on any connected gamepad, triggers the action.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Binding {
+    pub keys: Vec<KeyCode>,
+    pub button: Option<GamepadButtonType>,
+}
+
+impl Binding {
+    fn new(keys: &[KeyCode], button: GamepadButtonType) -> Self {
+        Binding {
+            keys: keys.to_vec(),
+            button: Some(button),
+        }
+    }
+
+    /// The name to show in prompts: the first key, or the button if there are no keys.
+    pub fn primary_name(&self) -> String {
+        match (self.keys.first(), self.button) {
+            (Some(key), _) => format!("{:?}", key),
+            (None, Some(button)) => format!("{:?}", button),
+            (None, None) => "Unbound".to_string(),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        let mut names: Vec<String> = self.keys.iter().map(|key| format!("{:?}", key)).collect();
+        if let Some(button) = self.button {
+            names.push(format!("{:?}", button));
+        }
+        if names.is_empty() {
+            "Unbound".to_string()
+        } else {
+            names.join(" / ")
+        }
+    }
+}
+
+/// The player's control scheme, saved to `controls.ron` whenever it's changed in settings.
+/// Actions missing from the file keep their default binding.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub move_up: Binding,
+    pub move_down: Binding,
+    pub move_left: Binding,
+    pub move_right: Binding,
+    pub sprint: Binding,
+    pub sneak: Binding,
+    pub interact: Binding,
+    pub pause: Binding,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        InputBindings {
+            move_up: Binding::new(&[KeyCode::Up, KeyCode::W], GamepadButtonType::DPadUp),
+            move_down: Binding::new(&[KeyCode::Down, KeyCode::S], GamepadButtonType::DPadDown),
+            move_left: Binding::new(&[KeyCode::Left, KeyCode::A], GamepadButtonType::DPadLeft),
+            move_right: Binding::new(&[KeyCode::Right, KeyCode::D], GamepadButtonType::DPadRight),
+            sprint: Binding::new(&[KeyCode::LShift], GamepadButtonType::RightTrigger),
+            sneak: Binding::new(&[KeyCode::LControl], GamepadButtonType::LeftTrigger),
+            interact: Binding::new(&[KeyCode::E], GamepadButtonType::West),
+            pause: Binding::new(&[KeyCode::Escape], GamepadButtonType::Start),
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn load() -> Self {
+        load_ron(&save_path(BINDINGS_FILE)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        save_ron(&save_path(BINDINGS_FILE), self);
+    }
+
+    pub fn get(&self, action: Action) -> &Binding {
+        match action {
+            Action::MoveUp => &self.move_up,
+            Action::MoveDown => &self.move_down,
+            Action::MoveLeft => &self.move_left,
+            Action::MoveRight => &self.move_right,
+            Action::Sprint => &self.sprint,
+            Action::Sneak => &self.sneak,
+            Action::Interact => &self.interact,
+            Action::Pause => &self.pause,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: Action) -> &mut Binding {
+        match action {
+            Action::MoveUp => &mut self.move_up,
+            Action::MoveDown => &mut self.move_down,
+            Action::MoveLeft => &mut self.move_left,
+            Action::MoveRight => &mut self.move_right,
+            Action::Sprint => &mut self.sprint,
+            Action::Sneak => &mut self.sneak,
+            Action::Interact => &mut self.interact,
+            Action::Pause => &mut self.pause,
+        }
+    }
+
+    pub fn pressed(
+        &self,
+        action: Action,
+        keys: &Input<KeyCode>,
+        gamepads: &Gamepads,
+        gamepad_buttons: &Input<GamepadButton>,
+    ) -> bool {
+        let binding = self.get(action);
+        binding.keys.iter().any(|key| keys.pressed(*key))
+            || binding.button.is_some_and(|button| {
+                gamepads
+                    .iter()
+                    .any(|gamepad| gamepad_buttons.pressed(GamepadButton::new(*gamepad, button)))
+            })
+    }
+
+    pub fn just_pressed(
+        &self,
+        action: Action,
+        keys: &Input<KeyCode>,
+        gamepads: &Gamepads,
+        gamepad_buttons: &Input<GamepadButton>,
+    ) -> bool {
+        let binding = self.get(action);
+        binding.keys.iter().any(|key| keys.just_pressed(*key))
+            || binding.button.is_some_and(|button| {
+                gamepads.iter().any(|gamepad| {
+                    gamepad_buttons.just_pressed(GamepadButton::new(*gamepad, button))
+                })
+            })
+    }
+
+    /// Like `menu::take_press`, but for whatever is bound to `action`.
+    pub fn take_press(
+        &self,
+        action: Action,
+        keys: &mut Input<KeyCode>,
+        gamepads: &Gamepads,
+        gamepad_buttons: &mut Input<GamepadButton>,
+    ) -> bool {
+        let binding = self.get(action);
+        let mut pressed = false;
+        for key in &binding.keys {
+            pressed |= keys.clear_just_pressed(*key);
+        }
+        if let Some(button) = binding.button {
+            for gamepad in gamepads.iter() {
+                pressed |= gamepad_buttons.clear_just_pressed(GamepadButton::new(*gamepad, button));
+            }
+        }
+        pressed
+    }
+}
+
+/// This frame's input, already translated from devices into actions.
+#[derive(Default)]
+pub struct PlayerInput {
+    /// Desired direction of travel. Keys give whole steps on each axis, a stick gives how far
+    /// it's pushed, so a half-pushed stick walks at half speed.
+    pub movement: Vec2,
+    pub sprint: bool,
+    pub sneak: bool,
+    /// Pressed since a simulation step last acted on it. Steps don't line up with frames, so
+    /// the press is held until one has.
+    pub interact: bool,
+    /// How far to zoom this frame, in mouse-wheel notches. Positive zooms in.
+    pub zoom: f32,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn read_player_input(
+    time: Res<Time>,
+    keys: Res<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    bindings: Res<InputBindings>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    mut input: ResMut<PlayerInput>,
+) {
+    let pressed = |action| bindings.pressed(action, &keys, &gamepads, &gamepad_buttons);
+    let axis = |positive, negative| (pressed(positive) as i32 - pressed(negative) as i32) as f32;
+    let mut movement = Vec2::new(
+        axis(Action::MoveRight, Action::MoveLeft),
+        axis(Action::MoveUp, Action::MoveDown),
+    );
+
+    let mut zoom: f32 = mouse_wheel
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / PIXELS_PER_NOTCH,
+        })
+        .sum();
+
+    for gamepad in gamepads.iter() {
+        let zoom_stick = axes
+            .get(GamepadAxis::new(*gamepad, GamepadAxisType::RightStickY))
+            .unwrap_or(0.0);
+        if zoom_stick.abs() > STICK_DEADZONE {
+            zoom += zoom_stick * STICK_ZOOM_RATE * time.delta_seconds();
+        }
+
+        let stick = Vec2::new(
+            axes.get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickX))
+                .unwrap_or(0.0),
+            axes.get(GamepadAxis::new(*gamepad, GamepadAxisType::LeftStickY))
+                .unwrap_or(0.0),
+        );
+        let length = stick.length();
+        if length <= STICK_DEADZONE {
+            continue;
+        }
+        // Rescale past the deadzone so speed still ramps smoothly up from zero.
+        let magnitude = ((length - STICK_DEADZONE) / (1.0 - STICK_DEADZONE)).min(1.0);
+        if magnitude > movement.length() {
+            movement = stick / length * magnitude;
+        }
+    }
+
+    input.movement = movement;
+    input.sprint = pressed(Action::Sprint);
+    input.sneak = pressed(Action::Sneak);
+    input.interact |= bindings.just_pressed(Action::Interact, &keys, &gamepads, &gamepad_buttons);
+    input.zoom = zoom;
+}
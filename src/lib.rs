@@ -0,0 +1,44 @@
+//! Not Alone: lose the people following you in the crowd before you reach your errand.
+//! The game itself is in `main.rs`; this exposes its pieces so tests and other binaries can
+//! put together just the parts they need.
+pub mod audio;
+pub mod camera;
+pub mod components;
+pub mod config;
+pub mod crowd;
+pub mod debug_overlay;
+pub mod follower;
+pub mod gameplay;
+pub mod generation;
+pub mod headless;
+pub mod high_scores;
+pub mod hud;
+pub mod indicators;
+pub mod input;
+pub mod map;
+pub mod menu;
+pub mod minimap;
+pub mod movement;
+pub mod pause;
+pub mod player;
+pub mod replay;
+pub mod resources;
+pub mod results;
+pub mod save;
+pub mod settings;
+pub mod sfx;
+pub mod storage;
+pub mod systems;
+pub mod ui;
+pub mod user_settings;
+
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AppState {
+    Menu,
+    Game,
+    Paused,
+    Win,
+    Lose,
+    HighScores,
+    Settings,
+}
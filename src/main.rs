@@ -1,290 +1,84 @@
-//! Renders a 2D scene containing a single, moving sprite.
-use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
-
-mod components;
-mod generation;
-mod systems;
-
+//! Not Alone, the game: opens the window on the main menu, or straight into a run when given a
+//! replay to watch. With headless options it plays runs without a window instead.
 use bevy::prelude::*;
-use bevy_prototype_debug_lines::DebugLinesPlugin;
-use bevy_prototype_lyon::prelude::*;
-use bevy_rapier2d::parry::shape::Cuboid;
-use bevy_rapier2d::prelude::{
-    Collider, CollisionGroups, GravityScale, NoUserData, RapierConfiguration,
-    RapierDebugRenderPlugin, RapierPhysicsPlugin, Restitution, RigidBody, Velocity,
-};
-use components::*;
-use generation::{generate_bystander, get_buildings, player_init, Building};
-use rand::Rng;
-use systems::{
-    bystander_movement, camera_tracker, follower_system, handle_player_arrival_at_destination,
-    sprite_movement,
+use not_alone::{
+    audio::AudioPlugin,
+    config::GameConfig,
+    debug_overlay::DebugOverlayPlugin,
+    gameplay::GameplayPlugin,
+    headless::{self, HeadlessOptions},
+    input::InputBindings,
+    replay::{Playback, Replay, ReplayPlugin},
+    save::SavePlugin,
+    ui::UiPlugin,
+    user_settings::UserSettings,
+    AppState,
 };
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub enum AppState {
-    Menu,
-    Game,
-    Win,
-    Lose,
+/// Every flag the game understands, and whether it's followed by a value.
+const FLAGS: [(&str, bool); 6] = [
+    ("--config", true),
+    ("--replay", true),
+    ("--headless", false),
+    ("--runs", true),
+    ("--seed", true),
+    ("--difficulty", true),
+];
+
+/// Each part of the game picks out its own flags and ignores the rest, so without this a
+/// mistyped flag would be dropped without a word.
+fn warn_about_unknown_args() {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg.starts_with("--config=") {
+            continue;
+        }
+        match FLAGS.iter().find(|(flag, _)| *flag == arg) {
+            Some((_, true)) => {
+                args.next();
+            }
+            Some((_, false)) => {}
+            None => eprintln!("Ignoring unknown argument {:?}", arg),
+        }
+    }
 }
 
 fn main() {
-    App::new()
+    warn_about_unknown_args();
+    let replay = Replay::from_args();
+    let config = match &replay {
+        // A run only plays out the same with the tuning it was recorded with.
+        Some(replay) => replay.config.clone(),
+        None => GameConfig::from_args(),
+    };
+    if let Some(options) = HeadlessOptions::from_args() {
+        headless::run(config, options);
+        return;
+    }
+    let user_settings = UserSettings::load();
+    let mut app = App::new();
+    app.insert_resource(user_settings.window_descriptor())
+        .insert_resource(user_settings)
         .insert_resource(ClearColor(Color::rgb(0.0, 0.0, 0.0)))
         .add_plugins(DefaultPlugins)
-        .add_plugin(ShapePlugin)
-        // .add_plugin(LogDiagnosticsPlugin::default())
-        .add_plugin(RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(50.0))
-        // .add_plugin(RapierDebugRenderPlugin::default())
-        // .add_plugin(FrameTimeDiagnosticsPlugin::default())
-        .add_plugin(DebugLinesPlugin::default())
-        .add_state(AppState::Menu)
-        //Main Screen Systems
-        .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(main_menu_setup))
-        .add_system_set(SystemSet::on_update(AppState::Menu).with_system(main_menu_space_to_start))
-        .add_system_set(SystemSet::on_exit(AppState::Menu).with_system(main_menu_cleanup))
-        // Game Systems
-        .add_system_set(SystemSet::on_enter(AppState::Game).with_system(setup))
-        .add_system_set(
-            SystemSet::on_update(AppState::Game)
-                .with_system(sprite_movement)
-                .with_system(bystander_movement)
-                .with_system(camera_tracker)
-                .with_system(follower_system)
-                .with_system(handle_player_arrival_at_destination),
-        )
-        // Lose System
-        .add_system_set(SystemSet::on_exit(AppState::Lose).with_system(main_menu_cleanup))
-        .add_system_set(SystemSet::on_update(AppState::Lose).with_system(win_lose_space_to_main))
-        .add_system_set(SystemSet::on_enter(AppState::Lose).with_system(lose_setup))
-        // Win System
-        .add_system_set(SystemSet::on_exit(AppState::Win).with_system(main_menu_cleanup))
-        .add_system_set(SystemSet::on_update(AppState::Win).with_system(win_lose_space_to_main))
-        .add_system_set(SystemSet::on_enter(AppState::Win).with_system(win_setup))
-        .run();
-}
-
-fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn_bundle(Camera2dBundle::default());
-    commands.spawn_bundle(SpriteBundle {
-        texture: asset_server.load("main_menu.png"),
-        transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
-        ..default()
-    });
-}
-
-fn main_menu_space_to_start(keys: Res<Input<KeyCode>>, mut app_state: ResMut<State<AppState>>) {
-    if keys.pressed(KeyCode::Space) {
-        app_state.set(AppState::Game).unwrap();
-    }
-}
-
-fn win_lose_space_to_main(keys: Res<Input<KeyCode>>, mut app_state: ResMut<State<AppState>>) {
-    if keys.pressed(KeyCode::Space) {
-        app_state.set(AppState::Menu).unwrap();
+        .insert_resource(config)
+        .insert_resource(InputBindings::load());
+    // A replay goes straight into the run it recorded.
+    match replay {
+        Some(replay) => {
+            app.insert_resource(replay.run_config())
+                .insert_resource(Playback::new(replay))
+                .add_state(AppState::Game);
+        }
+        None => {
+            app.add_state(AppState::Menu);
+        }
     }
-}
-
-fn win_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn_bundle(Camera2dBundle::default());
-    commands.spawn_bundle(SpriteBundle {
-        texture: asset_server.load("win_splash.png"),
-        transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
-        ..default()
-    });
-}
-
-fn lose_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn_bundle(Camera2dBundle::default());
-    commands.spawn_bundle(SpriteBundle {
-        texture: asset_server.load("lose_splash.png"),
-        transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
-        ..default()
-    });
-}
-fn main_menu_cleanup(mut commands: Commands, mut entity: Query<Entity>) {
-    for entity in entity.iter_mut() {
-        commands.entity(entity).despawn();
-    }
-}
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let shape = shapes::RegularPolygon {
-        sides: 6,
-        feature: shapes::RegularPolygonFeature::Radius(10.0),
-        ..shapes::RegularPolygon::default()
-    };
-    commands.spawn_bundle(Camera2dBundle::default());
-    commands.spawn_bundle(SpriteBundle {
-        texture: asset_server.load("map.png"),
-        transform: Transform::from_xyz(1920. / 2., 1080. / 2., 0.),
-        ..default()
-    });
-    build_walls(&mut commands);
-    create_player(&mut commands, shape.clone(), asset_server);
-
-    create_bystanders(&mut commands);
-    create_buildings(&mut commands);
-
-    create_follower(commands, shape);
-}
-
-fn create_buildings(commands: &mut Commands) {
-    get_buildings()
-        .iter()
-        .for_each(|building| building.add_to_scene(commands));
-}
-
-fn create_follower(mut commands: Commands, shape: RegularPolygon) {
-    let mut rng = rand::thread_rng();
-    let eight_byte_range = 0.0..1.0;
-    let fill_color = Color::rgb(
-        rng.gen_range(eight_byte_range.clone()),
-        rng.gen_range(eight_byte_range.clone()),
-        rng.gen_range(eight_byte_range.clone()),
-    );
-    let stroke_color = Color::rgb(
-        rng.gen_range(eight_byte_range.clone()),
-        rng.gen_range(eight_byte_range.clone()),
-        rng.gen_range(eight_byte_range.clone()),
-    );
-    commands
-        .spawn_bundle(GeometryBuilder::build_as(
-            &shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(fill_color),
-                outline_mode: StrokeMode::new(stroke_color, 5.0),
-            },
-            Transform::from_xyz(500., 500., 4.),
-        ))
-        .insert(RigidBody::Dynamic)
-        .insert(GravityScale(0.0))
-        .insert(Collider::cuboid(10.0, 10.0))
-        .insert(Follower)
-        .insert(Velocity {
-            linvel: Vec2::new(1.0, 2.0),
-            angvel: 0.2,
-        });
-}
-
-fn build_walls(commands: &mut Commands) {
-    commands
-        .spawn()
-        .insert(RigidBody::Fixed)
-        .insert(Collider::cuboid(1920.0, 5.0))
-        .insert_bundle(TransformBundle::from(Transform::from_xyz(
-            1920.0 / 2.0,
-            -5.0,
-            0.0,
-        )));
-    commands
-        .spawn()
-        .insert(RigidBody::Fixed)
-        .insert(Collider::cuboid(1920.0, 5.0))
-        .insert_bundle(TransformBundle::from(Transform::from_xyz(
-            1920.0 / 2.0,
-            1080.0,
-            0.0,
-        )));
-    commands
-        .spawn()
-        .insert(RigidBody::Fixed)
-        .insert(Collider::cuboid(5.0, 1080.0))
-        .insert_bundle(TransformBundle::from(Transform::from_xyz(
-            0.0,
-            1080.0 / 2.0,
-            0.0,
-        )));
-    commands
-        .spawn()
-        .insert(RigidBody::Fixed)
-        .insert(Collider::cuboid(5.0, 1080.0))
-        .insert_bundle(TransformBundle::from(Transform::from_xyz(
-            1920.0,
-            1080.0 / 2.0,
-            0.0,
-        )));
-}
-
-fn create_bystanders(commands: &mut Commands) {
-    (1..1000).for_each(|_| {
-        let bystander = generate_bystander();
-        commands
-            .spawn_bundle(GeometryBuilder::build_as(
-                &shapes::RegularPolygon {
-                    sides: bystander.side_count,
-                    feature: shapes::RegularPolygonFeature::Radius(10.0),
-                    ..shapes::RegularPolygon::default()
-                },
-                DrawMode::Outlined {
-                    fill_mode: FillMode::color(bystander.fill_color),
-                    outline_mode: StrokeMode::new(bystander.stroke_color, 5.0),
-                },
-                Transform::from_translation(bystander.start_location),
-            ))
-            .insert(Bystander {
-                destination: bystander.destination,
-                destination_building: bystander.destination_building,
-                focus: bystander.focus,
-            })
-            .insert(CollisionGroups::new(0b1, 0b1))
-            .insert(RigidBody::Dynamic)
-            .insert(Restitution::coefficient(0.01))
-            .insert(GravityScale(0.0))
-            .insert(Collider::cuboid(10.0, 10.0))
-            .insert(Velocity {
-                linvel: Vec2::new(1.0, 2.0),
-                angvel: 0.2,
-            });
-    });
-}
-
-fn create_player(commands: &mut Commands, shape: RegularPolygon, asset_server: Res<AssetServer>) {
-    let player_init = player_init();
-    commands.spawn_bundle(
-        // Create a TextBundle that has a Text with a single section.
-        TextBundle::from_section(
-            // Accepts a `String` or any type that converts into a `String`, such as `&str`
-            format!(
-                "Get To {}, Don't Let Them Follow You!",
-                player_init.destination.name
-            ),
-            TextStyle {
-                font: asset_server.load("fonts/Akira Expanded Demo.otf"),
-                font_size: 23.0,
-                color: Color::WHITE,
-            },
-        ) // Set the alignment of the Text
-        .with_text_alignment(TextAlignment::TOP_CENTER)
-        // Set the style of the TextBundle itself.
-        .with_style(Style {
-            align_self: AlignSelf::FlexEnd,
-            position_type: PositionType::Absolute,
-            position: UiRect {
-                bottom: Val::Px(5.0),
-                right: Val::Px(15.0),
-                ..default()
-            },
-            ..default()
-        }),
-    );
-    commands
-        .spawn_bundle(GeometryBuilder::build_as(
-            &shape,
-            DrawMode::Outlined {
-                fill_mode: FillMode::color(Color::CYAN),
-                outline_mode: StrokeMode::new(Color::BLACK, 5.0),
-            },
-            Transform::from_xyz(50., 10., 4.),
-        ))
-        .insert(RigidBody::Dynamic)
-        .insert(GravityScale(0.0))
-        .insert(Collider::cuboid(10.0, 10.0))
-        .insert(Player {
-            destination: player_init.destination,
-        })
-        .insert(Velocity {
-            linvel: Vec2::new(1.0, 2.0),
-            angvel: 0.2,
-        });
+    app.add_plugin(GameplayPlugin)
+        .add_plugin(UiPlugin)
+        .add_plugin(AudioPlugin)
+        .add_plugin(ReplayPlugin)
+        .add_plugin(SavePlugin)
+        .add_plugin(DebugOverlayPlugin)
+        .run();
 }
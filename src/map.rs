@@ -0,0 +1,97 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{Collider, RigidBody};
+
+use crate::{
+    components::GameEntity,
+    gameplay::SpawnSystem,
+    generation::{get_buildings, map_info},
+    resources::RunConfig,
+    AppState,
+};
+
+/// The streets: the boundary walls, the buildings and their entrances, and the map artwork.
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Game)
+                .with_system(spawn_map.label(SpawnSystem::Map))
+                .with_system(spawn_backdrop),
+        );
+    }
+}
+
+fn spawn_map(mut commands: Commands, run_config: Res<RunConfig>) {
+    build_walls(&mut commands);
+    create_buildings(&mut commands, run_config.map);
+}
+
+/// The map artwork behind a run; the buildings themselves are invisible colliders. Skipped
+/// when there's no asset server, as in headless runs.
+fn spawn_backdrop(
+    mut commands: Commands,
+    run_config: Res<RunConfig>,
+    asset_server: Option<Res<AssetServer>>,
+) {
+    let asset_server = match asset_server {
+        Some(asset_server) => asset_server,
+        None => return,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load(map_info(run_config.map).texture),
+            transform: Transform::from_xyz(1920. / 2., 1080. / 2., 0.),
+            ..default()
+        })
+        .insert(GameEntity);
+}
+
+fn create_buildings(commands: &mut Commands, map: usize) {
+    get_buildings(map)
+        .iter()
+        .for_each(|building| building.add_to_scene(commands));
+}
+
+fn build_walls(commands: &mut Commands) {
+    commands
+        .spawn()
+        .insert(GameEntity)
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(1920.0, 5.0))
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(
+            1920.0 / 2.0,
+            -5.0,
+            0.0,
+        )));
+    commands
+        .spawn()
+        .insert(GameEntity)
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(1920.0, 5.0))
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(
+            1920.0 / 2.0,
+            1080.0,
+            0.0,
+        )));
+    commands
+        .spawn()
+        .insert(GameEntity)
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(5.0, 1080.0))
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(
+            0.0,
+            1080.0 / 2.0,
+            0.0,
+        )));
+    commands
+        .spawn()
+        .insert(GameEntity)
+        .insert(RigidBody::Fixed)
+        .insert(Collider::cuboid(5.0, 1080.0))
+        .insert_bundle(TransformBundle::from(Transform::from_xyz(
+            1920.0,
+            1080.0 / 2.0,
+            0.0,
+        )));
+}
@@ -0,0 +1,371 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    config::Difficulty,
+    generation::{map_info, MAPS},
+    resources::{RunConfig, SelectedMap, SelectedSeed},
+    save::{ResumableRun, ResumedRun, SavedRun},
+    AppState,
+};
+
+/// Anything spawned for the main menu, removed again when the menu is left.
+#[derive(Component)]
+pub struct MenuEntity;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MenuItem {
+    Play,
+    Continue,
+    Difficulty,
+    Map,
+    Seed,
+    Settings,
+    HighScores,
+    Quit,
+}
+
+impl MenuItem {
+    pub const ALL: [MenuItem; 8] = [
+        MenuItem::Play,
+        MenuItem::Continue,
+        MenuItem::Difficulty,
+        MenuItem::Map,
+        MenuItem::Seed,
+        MenuItem::Settings,
+        MenuItem::HighScores,
+        MenuItem::Quit,
+    ];
+}
+
+/// Index into `MenuItem::ALL` of the highlighted entry.
+#[derive(Default)]
+pub struct MenuSelection(pub usize);
+
+/// Text colour of the highlighted entry, on every screen with a list to pick from.
+pub const SELECTED_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+/// Text colour of entries that can't be used right now, like Continue with no saved run.
+const DISABLED_COLOR: Color = Color::GRAY;
+const BUTTON_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const SELECTED_BUTTON_COLOR: Color = Color::rgba(0.2, 0.2, 0.2, 0.8);
+
+pub fn main_menu_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+        font_size: 22.0,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("main_menu.png"),
+            transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
+            ..default()
+        })
+        .insert(MenuEntity);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                // UI flows bottom-up, so reverse the column to read top to bottom.
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::FlexEnd,
+                padding: UiRect::all(Val::Px(40.0)),
+                ..default()
+            },
+            color: Color::NONE.into(),
+            ..default()
+        })
+        .insert(MenuEntity)
+        .with_children(|parent| {
+            for item in MenuItem::ALL {
+                parent
+                    .spawn_bundle(ButtonBundle {
+                        style: Style {
+                            margin: UiRect::all(Val::Px(4.0)),
+                            padding: UiRect::all(Val::Px(8.0)),
+                            ..default()
+                        },
+                        color: BUTTON_COLOR.into(),
+                        ..default()
+                    })
+                    .insert(item)
+                    .with_children(|button| {
+                        // The label is filled in by `update_menu_labels`.
+                        button.spawn_bundle(TextBundle::from_section("", style.clone()));
+                    });
+            }
+        });
+    commands.insert_resource(MenuSelection::default());
+}
+
+/// Checks for a fresh press of `key`, or of `button` on any gamepad, and consumes it. Screens
+/// entered during a state change update on the same frame, so without this the press that
+/// opened a screen would also act on it.
+pub fn take_press(
+    keys: &mut Input<KeyCode>,
+    gamepads: &Gamepads,
+    gamepad_buttons: &mut Input<GamepadButton>,
+    key: KeyCode,
+    button: GamepadButtonType,
+) -> bool {
+    let mut pressed = keys.clear_just_pressed(key);
+    for gamepad in gamepads.iter() {
+        pressed |= gamepad_buttons.clear_just_pressed(GamepadButton::new(*gamepad, button));
+    }
+    pressed
+}
+
+/// Moves `selection` through a list of `count` entries with Up and Down, wrapping around at
+/// either end. `pressed` is a screen's `take_press` for its own input.
+pub fn move_selection(
+    pressed: &mut impl FnMut(KeyCode, GamepadButtonType) -> bool,
+    selection: &mut usize,
+    count: usize,
+) {
+    if pressed(KeyCode::Up, GamepadButtonType::DPadUp) {
+        *selection = (*selection + count - 1) % count;
+    }
+    if pressed(KeyCode::Down, GamepadButtonType::DPadDown) {
+        *selection = (*selection + 1) % count;
+    }
+}
+
+/// Moves the highlight with the arrow keys or d-pad and acts on the highlighted entry with
+/// Enter or the gamepad's south button. Left and right change the value of an entry.
+#[allow(clippy::too_many_arguments)]
+pub fn main_menu_navigation(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut selection: ResMut<MenuSelection>,
+    mut difficulty: ResMut<Difficulty>,
+    mut map: ResMut<SelectedMap>,
+    mut seed: ResMut<SelectedSeed>,
+    mut run_config: ResMut<RunConfig>,
+    mut resumable: ResMut<ResumableRun>,
+    mut commands: Commands,
+    mut app_state: ResMut<State<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let mut pressed = |key: KeyCode, button: GamepadButtonType| {
+        take_press(&mut keys, &gamepads, &mut gamepad_buttons, key, button)
+    };
+    let item_count = MenuItem::ALL.len();
+    move_selection(&mut pressed, &mut selection.0, item_count);
+
+    let item = MenuItem::ALL[selection.0];
+    if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+        change_menu_value(item, false, &mut difficulty, &mut map);
+    }
+    if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+        change_menu_value(item, true, &mut difficulty, &mut map);
+    }
+    if pressed(KeyCode::Return, GamepadButtonType::South) {
+        activate_menu_item(
+            item,
+            &mut difficulty,
+            &mut map,
+            &mut seed,
+            &mut run_config,
+            &mut resumable,
+            &mut commands,
+            &mut app_state,
+            &mut exit,
+        );
+    }
+}
+
+/// Mouse support: hovering highlights an entry and clicking acts on it.
+#[allow(clippy::too_many_arguments)]
+pub fn main_menu_mouse(
+    interaction_query: Query<(&Interaction, &MenuItem), Changed<Interaction>>,
+    mut selection: ResMut<MenuSelection>,
+    mut difficulty: ResMut<Difficulty>,
+    mut map: ResMut<SelectedMap>,
+    mut seed: ResMut<SelectedSeed>,
+    mut run_config: ResMut<RunConfig>,
+    mut resumable: ResMut<ResumableRun>,
+    mut commands: Commands,
+    mut app_state: ResMut<State<AppState>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    for (interaction, item) in interaction_query.iter() {
+        let index = MenuItem::ALL.iter().position(|i| i == item).unwrap();
+        match interaction {
+            Interaction::Hovered => selection.0 = index,
+            Interaction::Clicked => {
+                selection.0 = index;
+                activate_menu_item(
+                    *item,
+                    &mut difficulty,
+                    &mut map,
+                    &mut seed,
+                    &mut run_config,
+                    &mut resumable,
+                    &mut commands,
+                    &mut app_state,
+                    &mut exit,
+                );
+            }
+            Interaction::None => {}
+        }
+    }
+}
+
+/// Typing digits while the seed entry is highlighted builds up a seed; backspace removes the
+/// last digit and clearing it goes back to a random seed.
+pub fn main_menu_seed_entry(
+    keys: Res<Input<KeyCode>>,
+    mut characters: EventReader<ReceivedCharacter>,
+    selection: Res<MenuSelection>,
+    mut seed: ResMut<SelectedSeed>,
+) {
+    let editing = MenuItem::ALL[selection.0] == MenuItem::Seed;
+    for character in characters.iter() {
+        if !editing {
+            continue;
+        }
+        if let Some(digit) = character.char.to_digit(10) {
+            let current = seed.0.unwrap_or(0);
+            if let Some(next) = current
+                .checked_mul(10)
+                .and_then(|value| value.checked_add(digit as u64))
+            {
+                seed.0 = Some(next);
+            }
+        }
+    }
+    if editing && keys.just_pressed(KeyCode::Back) {
+        seed.0 = match seed.0 {
+            Some(value) if value >= 10 => Some(value / 10),
+            _ => None,
+        };
+    }
+}
+
+fn change_menu_value(
+    item: MenuItem,
+    forward: bool,
+    difficulty: &mut Difficulty,
+    map: &mut SelectedMap,
+) {
+    match item {
+        MenuItem::Difficulty => {
+            *difficulty = if forward {
+                difficulty.next()
+            } else {
+                difficulty.previous()
+            };
+        }
+        MenuItem::Map => {
+            let count = MAPS.len();
+            map.0 = if forward {
+                (map.0 + 1) % count
+            } else {
+                (map.0 + count - 1) % count
+            };
+        }
+        _ => {}
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn activate_menu_item(
+    item: MenuItem,
+    difficulty: &mut Difficulty,
+    map: &mut SelectedMap,
+    seed: &mut SelectedSeed,
+    run_config: &mut RunConfig,
+    resumable: &mut ResumableRun,
+    commands: &mut Commands,
+    app_state: &mut State<AppState>,
+    exit: &mut EventWriter<AppExit>,
+) {
+    match item {
+        MenuItem::Play => {
+            *run_config = RunConfig {
+                seed: seed.0.unwrap_or_else(rand::random),
+                difficulty: *difficulty,
+                map: map.0,
+            };
+            let _ = app_state.set(AppState::Game);
+        }
+        MenuItem::Continue => {
+            if let Some(saved) = resumable.0.take() {
+                // A run can only be continued once, however it turns out.
+                SavedRun::delete();
+                *run_config = saved.run_config();
+                commands.insert_resource(ResumedRun(saved));
+                let _ = app_state.set(AppState::Game);
+            }
+        }
+        MenuItem::Difficulty | MenuItem::Map => change_menu_value(item, true, difficulty, map),
+        // Gamepads can't type, so confirming the seed entry rolls a fresh one instead.
+        MenuItem::Seed => seed.0 = Some(rand::random()),
+        MenuItem::Settings => {
+            let _ = app_state.set(AppState::Settings);
+        }
+        MenuItem::HighScores => {
+            let _ = app_state.set(AppState::HighScores);
+        }
+        MenuItem::Quit => exit.send(AppExit),
+    }
+}
+
+fn menu_label(
+    item: MenuItem,
+    difficulty: Difficulty,
+    map: &SelectedMap,
+    seed: &SelectedSeed,
+) -> String {
+    match item {
+        MenuItem::Play => "Play".to_string(),
+        MenuItem::Continue => "Continue".to_string(),
+        MenuItem::Difficulty => format!("< Difficulty {} >", difficulty.name()),
+        MenuItem::Map => format!("< Map {} >", map_info(map.0).name),
+        MenuItem::Seed => match seed.0 {
+            Some(value) => format!("Seed {}", value),
+            None => "Seed Random".to_string(),
+        },
+        MenuItem::Settings => "Settings".to_string(),
+        MenuItem::HighScores => "High Scores".to_string(),
+        MenuItem::Quit => "Quit".to_string(),
+    }
+}
+
+pub fn update_menu_labels(
+    selection: Res<MenuSelection>,
+    difficulty: Res<Difficulty>,
+    map: Res<SelectedMap>,
+    seed: Res<SelectedSeed>,
+    resumable: Res<ResumableRun>,
+    mut button_query: Query<(&MenuItem, &Children, &mut UiColor)>,
+    mut text_query: Query<&mut Text>,
+) {
+    if !(selection.is_changed()
+        || difficulty.is_changed()
+        || map.is_changed()
+        || seed.is_changed()
+        || resumable.is_changed())
+    {
+        return;
+    }
+    for (item, children, mut color) in button_query.iter_mut() {
+        let selected = MenuItem::ALL[selection.0] == *item;
+        *color = if selected {
+            SELECTED_BUTTON_COLOR.into()
+        } else {
+            BUTTON_COLOR.into()
+        };
+        if let Ok(mut text) = text_query.get_mut(children[0]) {
+            text.sections[0].value = menu_label(*item, *difficulty, &map, &seed);
+            text.sections[0].style.color = if *item == MenuItem::Continue && resumable.0.is_none() {
+                DISABLED_COLOR
+            } else if selected {
+                SELECTED_COLOR
+            } else {
+                Color::WHITE
+            };
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+
+use crate::{
+    camera::half_view_size,
+    components::{Follower, GameEntity, MainCamera, Player},
+    config::GameConfig,
+    generation::{get_buildings, MAP_HEIGHT, MAP_WIDTH},
+    resources::RunConfig,
+    user_settings::Highlight,
+};
+
+/// Minimap pixels per map pixel.
+const MINIMAP_SCALE: f32 = 0.125;
+const MINIMAP_MARGIN: f32 = 10.0;
+const DOT_SIZE: f32 = 6.0;
+
+const BACKGROUND_COLOR: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+const BUILDING_COLOR: Color = Color::rgba(0.6, 0.6, 0.6, 0.9);
+const DESTINATION_COLOR: Color = Color::rgb(1.0, 0.85, 0.2);
+/// Dots start out in the default palette and are recoloured by `apply_ui_highlights`.
+const PLAYER_COLOR: Color = Color::CYAN;
+const FOLLOWER_COLOR: Color = Color::rgb(0.9, 0.2, 0.2);
+
+#[derive(Component)]
+pub struct MinimapPlayer;
+
+/// Marks where the follower was when it was last on screen.
+#[derive(Component)]
+pub struct MinimapFollower(pub Entity);
+
+fn minimap_rect(min: Vec2, max: Vec2, color: Color) -> NodeBundle {
+    let size = (max - min) * MINIMAP_SCALE;
+    NodeBundle {
+        style: Style {
+            position_type: PositionType::Absolute,
+            position: UiRect {
+                left: Val::Px(min.x * MINIMAP_SCALE),
+                bottom: Val::Px(min.y * MINIMAP_SCALE),
+                ..default()
+            },
+            size: Size::new(Val::Px(size.x), Val::Px(size.y)),
+            ..default()
+        },
+        color: color.into(),
+        ..default()
+    }
+}
+
+fn dot(location: Vec2, color: Color) -> NodeBundle {
+    let half = Vec2::splat(DOT_SIZE / 2.0 / MINIMAP_SCALE);
+    minimap_rect(location - half, location + half, color)
+}
+
+/// Places a dot so it's centred on `location`, in map pixels.
+fn move_dot(style: &mut Style, location: Vec2) {
+    style.position.left = Val::Px(location.x * MINIMAP_SCALE - DOT_SIZE / 2.0);
+    style.position.bottom = Val::Px(location.y * MINIMAP_SCALE - DOT_SIZE / 2.0);
+}
+
+/// Draws the minimap in the top-left corner once the player exists, since the destination
+/// isn't known until then.
+pub fn spawn_minimap(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    player_query: Query<(&Player, &Transform), Added<Player>>,
+    follower_query: Query<Entity, With<Follower>>,
+) {
+    let (player, player_transform) = match player_query.get_single() {
+        Ok(player) => player,
+        Err(_) => return,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(MINIMAP_MARGIN),
+                    top: Val::Px(MINIMAP_MARGIN),
+                    ..default()
+                },
+                size: Size::new(
+                    Val::Px(MAP_WIDTH * MINIMAP_SCALE),
+                    Val::Px(MAP_HEIGHT * MINIMAP_SCALE),
+                ),
+                ..default()
+            },
+            color: BACKGROUND_COLOR.into(),
+            ..default()
+        })
+        .insert(GameEntity)
+        .with_children(|parent| {
+            for building in get_buildings(run_config.map) {
+                parent.spawn_bundle(minimap_rect(
+                    building.bounds.min,
+                    building.bounds.max,
+                    BUILDING_COLOR,
+                ));
+            }
+            if let Some(entrance) = player.destination.entrance {
+                parent.spawn_bundle(minimap_rect(
+                    entrance.min - Vec2::splat(DOT_SIZE / MINIMAP_SCALE),
+                    entrance.max + Vec2::splat(DOT_SIZE / MINIMAP_SCALE),
+                    DESTINATION_COLOR,
+                ));
+            }
+            if config.minimap_show_followers {
+                for follower in follower_query.iter() {
+                    parent
+                        .spawn_bundle(NodeBundle {
+                            visibility: Visibility { is_visible: false },
+                            ..dot(Vec2::ZERO, FOLLOWER_COLOR)
+                        })
+                        .insert(MinimapFollower(follower))
+                        .insert(Highlight::Follower);
+                }
+            }
+            parent
+                .spawn_bundle(dot(player_transform.translation.truncate(), PLAYER_COLOR))
+                .insert(MinimapPlayer)
+                .insert(Highlight::Player);
+        });
+}
+
+pub fn update_minimap(
+    player_query: Query<&Transform, With<Player>>,
+    follower_query: Query<&Transform, With<Follower>>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut player_dot_query: Query<&mut Style, (With<MinimapPlayer>, Without<MinimapFollower>)>,
+    mut follower_dot_query: Query<(&MinimapFollower, &mut Style, &mut Visibility)>,
+) {
+    let player = player_query.single();
+    for mut style in &mut player_dot_query {
+        move_dot(&mut style, player.translation.truncate());
+    }
+
+    let (camera, projection) = camera_query.single();
+    let half_view = half_view_size(projection);
+    let view_center = camera.translation.truncate();
+    for (dot, mut style, mut visibility) in &mut follower_dot_query {
+        let follower = match follower_query.get(dot.0) {
+            Ok(follower) => follower.translation.truncate(),
+            Err(_) => continue,
+        };
+        let offset = (follower - view_center).abs();
+        if offset.x <= half_view.x && offset.y <= half_view.y {
+            move_dot(&mut style, follower);
+            visibility.is_visible = true;
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+use crate::{components::Walker, config::GameConfig, gameplay::STEP};
+
+/// Turns raw directional input into a movement vector no longer than 1, so diagonals are no
+/// faster than straight lines while a half-pushed stick still gives half speed.
+pub fn movement_intent(raw: Vec2) -> Vec2 {
+    raw.clamp_length_max(1.0)
+}
+
+/// Moves `current` towards `target` by at most `max_delta`.
+fn approach(current: Vec2, target: Vec2, max_delta: f32) -> Vec2 {
+    let difference = target - current;
+    let distance = difference.length();
+    if distance <= max_delta || distance == 0.0 {
+        target
+    } else {
+        current + difference / distance * max_delta
+    }
+}
+
+/// Accelerates every walker towards the speed its controller asked for, and moves it. Speeding
+/// up uses the acceleration rate, slowing down or turning away uses the deceleration rate.
+pub fn walk(config: Res<GameConfig>, mut walker_query: Query<(&mut Transform, &mut Walker)>) {
+    for (mut transform, mut walker) in &mut walker_query {
+        let speeding_up = walker.target_velocity.dot(walker.velocity) >= 0.0
+            && walker.target_velocity.length_squared() > walker.velocity.length_squared();
+        let rate = if speeding_up {
+            config.acceleration
+        } else {
+            config.deceleration
+        };
+        walker.velocity = approach(walker.velocity, walker.target_velocity, rate * STEP);
+        transform.translation += (walker.velocity * STEP).extend(0.0);
+    }
+}
@@ -0,0 +1,185 @@
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::RapierConfiguration;
+
+use crate::{
+    input::{Action, InputBindings},
+    menu::{move_selection, take_press, SELECTED_COLOR},
+    save::RunSnapshot,
+    AppState,
+};
+
+/// The pause overlay, spawned while the game is paused and hidden while a screen is on top of it.
+#[derive(Component)]
+pub struct PauseEntity;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PauseItem {
+    Resume,
+    Restart,
+    Settings,
+    SaveAndQuit,
+    QuitToMenu,
+}
+
+impl PauseItem {
+    pub const ALL: [PauseItem; 5] = [
+        PauseItem::Resume,
+        PauseItem::Restart,
+        PauseItem::Settings,
+        PauseItem::SaveAndQuit,
+        PauseItem::QuitToMenu,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            PauseItem::Resume => "Resume",
+            PauseItem::Restart => "Restart",
+            PauseItem::Settings => "Settings",
+            PauseItem::SaveAndQuit => "Save And Quit",
+            PauseItem::QuitToMenu => "Quit To Menu",
+        }
+    }
+}
+
+/// Index into `PauseItem::ALL` of the highlighted entry.
+#[derive(Default)]
+pub struct PauseSelection(pub usize);
+
+/// Whatever is bound to pause (Esc or the gamepad's start button by default) pauses a run in
+/// progress.
+pub fn game_pause_input(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    bindings: Res<InputBindings>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    if bindings.take_press(Action::Pause, &mut keys, &gamepads, &mut gamepad_buttons) {
+        let _ = app_state.push(AppState::Paused);
+    }
+}
+
+pub fn pause_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = false;
+}
+
+pub fn resume_physics(mut rapier_config: ResMut<RapierConfiguration>) {
+    rapier_config.physics_pipeline_active = true;
+}
+
+pub fn pause_menu_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    selection: Option<Res<PauseSelection>>,
+) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+        font_size: 28.0,
+        color: Color::WHITE,
+    };
+    let selected = selection.map(|selection| selection.0).unwrap_or(0);
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                // UI flows bottom-up, so reverse the column to read top to bottom.
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.7).into(),
+            ..default()
+        })
+        .insert(PauseEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section(
+                "Paused",
+                TextStyle {
+                    font_size: 48.0,
+                    ..style.clone()
+                },
+            ));
+            for (index, item) in PauseItem::ALL.into_iter().enumerate() {
+                let color = if index == selected {
+                    SELECTED_COLOR
+                } else {
+                    Color::WHITE
+                };
+                parent
+                    .spawn_bundle(TextBundle::from_section(
+                        item.label(),
+                        TextStyle {
+                            color,
+                            ..style.clone()
+                        },
+                    ))
+                    .insert(item);
+            }
+        });
+    commands.insert_resource(PauseSelection(selected));
+}
+
+pub fn pause_menu_navigation(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    bindings: Res<InputBindings>,
+    mut selection: ResMut<PauseSelection>,
+    mut app_state: ResMut<State<AppState>>,
+    snapshot: RunSnapshot,
+) {
+    let resume = bindings.take_press(Action::Pause, &mut keys, &gamepads, &mut gamepad_buttons);
+    let mut pressed = |key: KeyCode, button: GamepadButtonType| {
+        take_press(&mut keys, &gamepads, &mut gamepad_buttons, key, button)
+    };
+    let item_count = PauseItem::ALL.len();
+    move_selection(&mut pressed, &mut selection.0, item_count);
+
+    if resume || pressed(KeyCode::Escape, GamepadButtonType::East) {
+        let _ = app_state.pop();
+        return;
+    }
+    if !pressed(KeyCode::Return, GamepadButtonType::South) {
+        return;
+    }
+    match PauseItem::ALL[selection.0] {
+        PauseItem::Resume => {
+            let _ = app_state.pop();
+        }
+        PauseItem::Restart => {
+            // Replacing the whole stack exits and re-enters the game, regenerating the run from
+            // the unchanged `RunConfig`.
+            let _ = app_state.replace(AppState::Game);
+        }
+        PauseItem::Settings => {
+            let _ = app_state.push(AppState::Settings);
+        }
+        PauseItem::SaveAndQuit => {
+            if let Some(saved) = snapshot.capture() {
+                saved.save();
+            }
+            let _ = app_state.replace(AppState::Menu);
+        }
+        PauseItem::QuitToMenu => {
+            let _ = app_state.replace(AppState::Menu);
+        }
+    }
+}
+
+pub fn update_pause_labels(
+    selection: Res<PauseSelection>,
+    mut label_query: Query<(&PauseItem, &mut Text)>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    for (item, mut text) in label_query.iter_mut() {
+        text.sections[0].style.color = if PauseItem::ALL[selection.0] == *item {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        };
+    }
+}
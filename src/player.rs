@@ -0,0 +1,104 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::{Appearance, Hidden, Player},
+    config::GameConfig,
+    gameplay::{
+        spawn_agent, AddSimulationSystems, AgentState, GameplaySystem, SimulationStage,
+        SpawnSystem, AGENT_SIDES,
+    },
+    generation::{player_init, Building},
+    resources::{Errand, Errands, GameRng, RunConfig},
+    save::{saved_building, ResumedRun},
+    systems::{
+        enforce_run_limits, handle_player_arrival_at_destination, player_hiding, sprite_movement,
+    },
+    user_settings::Highlight,
+    AppState,
+};
+
+/// The player: their errand, steering, stamina and hiding, and the checks that end the run.
+pub struct PlayerPlugin;
+
+impl Plugin for PlayerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Game).with_system(
+                spawn_player
+                    .label(SpawnSystem::Player)
+                    .after(SpawnSystem::Map),
+            ),
+        )
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new()
+                .label(GameplaySystem::Ai)
+                .with_system(player_hiding)
+                .with_system(sprite_movement.after(player_hiding)),
+        )
+        .add_simulation_system_set(
+            SimulationStage::AfterPhysics,
+            SystemSet::new()
+                .label(GameplaySystem::Rules)
+                .after(GameplaySystem::Perception)
+                .with_system(handle_player_arrival_at_destination)
+                // Reaching the door on the same frame the time runs out still counts.
+                .with_system(enforce_run_limits.after(handle_player_arrival_at_destination)),
+        );
+    }
+}
+
+fn spawn_player(
+    mut commands: Commands,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    resumed: Option<Res<ResumedRun>>,
+) {
+    if let Some(resumed) = resumed {
+        let saved = &resumed.0.player;
+        create_player(
+            &mut commands,
+            &config,
+            saved_building(run_config.map, &saved.destination),
+            &saved.agent,
+            saved.hidden,
+        );
+        return;
+    }
+    let player_init = player_init(&mut rng.0, run_config.map);
+    commands.insert_resource(Errands(vec![Errand {
+        building: player_init.destination.name.clone(),
+        done: false,
+    }]));
+    let appearance = Appearance {
+        sides: AGENT_SIDES,
+        fill_color: Color::CYAN,
+        stroke_color: Color::BLACK,
+    };
+    create_player(
+        &mut commands,
+        &config,
+        player_init.destination,
+        &AgentState::new(Vec3::new(50., 10., 4.), appearance),
+        false,
+    );
+}
+
+fn create_player(
+    commands: &mut Commands,
+    config: &GameConfig,
+    destination: Building,
+    state: &AgentState,
+    hidden: bool,
+) {
+    let mut player = spawn_agent(commands, config, state);
+    player
+        .insert(Player { destination })
+        .insert(Highlight::Player);
+    if hidden {
+        player
+            .insert(Hidden)
+            .insert(Visibility { is_visible: false });
+    }
+}
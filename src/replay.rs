@@ -0,0 +1,460 @@
+//! Recording runs and playing them back. Every run is recorded as what generated it plus the
+//! player's input on each simulation step, and saved to `replay.ron` when it ends. Starting the
+//! game with `--replay <path>` plays one back exactly: Space pauses, Left and Right skip back
+//! and forward, and Up and Down change the speed.
+use std::{fs, path::Path};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::GameEntity,
+    config::{Difficulty, GameConfig},
+    gameplay::{
+        in_game, queue_frame_steps, AddSimulationSystems, GameplayStage, GameplaySystem,
+        PendingSteps, SimulationClock, SimulationStage, STEP,
+    },
+    generation::MAPS,
+    input::PlayerInput,
+    resources::RunConfig,
+    save::ResumedRun,
+    storage::{save_path, save_ron},
+    AppState,
+};
+
+pub const REPLAY_FILE: &str = "replay.ron";
+
+/// Stick positions are stored as whole steps out of this many each way on each axis, finer than
+/// anyone can hold a stick.
+const MOVEMENT_RESOLUTION: f32 = 127.0;
+/// How far Left and Right skip, in seconds of the run.
+const SKIP_SECONDS: f32 = 5.0;
+/// The playback speeds Up and Down step through.
+const SPEEDS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+const NORMAL_SPEED: usize = 2;
+/// Most steps simulated in one frame, so the window keeps responding through a long skip.
+const MAX_STEPS_PER_FRAME: usize = 600;
+
+/// The player's input on one step, quantised so it's small to store and plays back exactly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordedInput {
+    pub movement: (i8, i8),
+    pub sprint: bool,
+    pub sneak: bool,
+    pub interact: bool,
+}
+
+impl RecordedInput {
+    pub fn from_input(input: &PlayerInput) -> Self {
+        let quantise = |value: f32| (value.clamp(-1.0, 1.0) * MOVEMENT_RESOLUTION).round() as i8;
+        RecordedInput {
+            movement: (quantise(input.movement.x), quantise(input.movement.y)),
+            sprint: input.sprint,
+            sneak: input.sneak,
+            interact: input.interact,
+        }
+    }
+
+    /// Writes this into `input`. The zoom is left alone, since the camera isn't part of the run.
+    pub fn apply(&self, input: &mut PlayerInput) {
+        input.movement =
+            Vec2::new(self.movement.0 as f32, self.movement.1 as f32) / MOVEMENT_RESOLUTION;
+        input.sprint = self.sprint;
+        input.sneak = self.sneak;
+        input.interact = self.interact;
+    }
+}
+
+/// A stretch of steps with the same input. Input rarely changes from one step to the next, so
+/// this keeps replays small.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputSpan {
+    pub steps: u32,
+    pub input: RecordedInput,
+}
+
+/// Everything needed to play a run again exactly: what generated it, the tuning it was played
+/// with, and the player's input on each simulation step.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub map: usize,
+    pub config: GameConfig,
+    pub inputs: Vec<InputSpan>,
+}
+
+impl Replay {
+    pub fn new(config: &GameConfig, run_config: &RunConfig) -> Self {
+        Replay {
+            seed: run_config.seed,
+            difficulty: run_config.difficulty,
+            map: run_config.map,
+            config: config.clone(),
+            inputs: Vec::new(),
+        }
+    }
+
+    /// Loads the replay named by `--replay <path>`, if there is one. Like the config, this runs
+    /// before the app exists, so problems go straight to stderr.
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip(1);
+        while let Some(arg) = args.next() {
+            if arg != "--replay" {
+                continue;
+            }
+            let path = args.next()?;
+            return match Replay::load(Path::new(&path)) {
+                Ok(replay) => Some(replay),
+                Err(error) => {
+                    eprintln!("Couldn't load replay {}: {}", path, error);
+                    None
+                }
+            };
+        }
+        None
+    }
+
+    /// Unlike settings, a replay that can't be read is left where it is; it's only ever read.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let replay: Replay = ron::from_str(&contents).map_err(|error| error.to_string())?;
+        if replay.map >= MAPS.len() {
+            return Err(format!("it's on map {}, which doesn't exist", replay.map));
+        }
+        Ok(replay)
+    }
+
+    pub fn save(&self, path: &Path) {
+        save_ron(path, self);
+    }
+
+    pub fn run_config(&self) -> RunConfig {
+        RunConfig {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            map: self.map,
+        }
+    }
+
+    /// Whether this replay was recorded from a run started with `run_config`.
+    pub fn matches(&self, run_config: &RunConfig) -> bool {
+        self.seed == run_config.seed
+            && self.difficulty == run_config.difficulty
+            && self.map == run_config.map
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.inputs.iter().map(|span| span.steps as usize).sum()
+    }
+
+    /// Adds the next step to the end of the recording.
+    pub fn push(&mut self, input: RecordedInput) {
+        match self.inputs.last_mut() {
+            Some(span) if span.input == input => span.steps += 1,
+            _ => self.inputs.push(InputSpan { steps: 1, input }),
+        }
+    }
+
+    /// The input on every step, in order.
+    pub fn step_inputs(&self) -> Vec<RecordedInput> {
+        self.inputs
+            .iter()
+            .flat_map(|span| std::iter::repeat_n(span.input, span.steps as usize))
+            .collect()
+    }
+}
+
+/// How far into a run `steps` steps take it, in seconds.
+fn duration(steps: usize) -> f32 {
+    steps as f32 * STEP
+}
+
+/// The first step at or after `seconds` into a run.
+fn step_at(seconds: f32) -> usize {
+    (seconds.max(0.0) / STEP).ceil() as usize
+}
+
+/// The run being played, recorded a step at a time.
+pub struct Recording(pub Replay);
+
+/// A replay being watched.
+pub struct Playback {
+    replay: Replay,
+    inputs: Vec<RecordedInput>,
+    pub paused: bool,
+    /// Index into `SPEEDS`.
+    speed: usize,
+    /// Run time owed at the current speed but not yet simulated, in seconds.
+    owed: f32,
+    /// The step a skip is heading for, while it's being fast-forwarded to.
+    skip_to: Option<usize>,
+}
+
+impl Playback {
+    pub fn new(replay: Replay) -> Self {
+        Playback {
+            inputs: replay.step_inputs(),
+            replay,
+            paused: false,
+            speed: NORMAL_SPEED,
+            owed: 0.0,
+            skip_to: None,
+        }
+    }
+
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+}
+
+/// Records every run, and plays back the replay in `Playback` when there is one. Needs
+/// `DefaultPlugins`, for the playback controls.
+pub struct ReplayPlugin;
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system_set(
+            SystemSet::on_enter(AppState::Game)
+                .with_system(start_recording)
+                .with_system(spawn_playback_label),
+        )
+        .add_system_set(
+            SystemSet::on_update(AppState::Game)
+                .with_system(playback_controls)
+                .with_system(
+                    queue_playback_steps
+                        .after(playback_controls)
+                        .after(queue_frame_steps),
+                ),
+        )
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new()
+                .label(GameplaySystem::Input)
+                .before(GameplaySystem::Ai)
+                .with_system(record_input)
+                .with_system(play_back_input),
+        )
+        .add_system_set_to_stage(
+            GameplayStage::AfterSimulation,
+            SystemSet::new()
+                .with_run_criteria(in_game)
+                .with_system(update_playback_label),
+        )
+        .add_system_set(SystemSet::on_exit(AppState::Game).with_system(save_recording))
+        // Whatever is started from the menu is played, not watched.
+        .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(stop_playback));
+    }
+}
+
+/// Starts recording a new run, unless it's the replay being watched. Anything else, like a new
+/// seed from the results screen, ends the playback and is played as normal. A continued run
+/// isn't recorded, since a replay has to start from the beginning.
+pub fn start_recording(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    playback: Option<Res<Playback>>,
+    resumed: Option<Res<ResumedRun>>,
+) {
+    if resumed.is_some() {
+        commands.remove_resource::<Playback>();
+        commands.remove_resource::<Recording>();
+        return;
+    }
+    match playback {
+        Some(playback) if playback.replay.matches(&run_config) => {}
+        _ => {
+            commands.remove_resource::<Playback>();
+            commands.insert_resource(Recording(Replay::new(&config, &run_config)));
+        }
+    }
+}
+
+/// Records this step's input, quantised first so the run plays out on exactly what's saved.
+pub fn record_input(mut input: ResMut<PlayerInput>, recording: Option<ResMut<Recording>>) {
+    if let Some(mut recording) = recording {
+        let recorded = RecordedInput::from_input(&input);
+        recorded.apply(&mut input);
+        recording.0.push(recorded);
+    }
+}
+
+/// Feeds the replay's input for this step to the player, in place of their devices'.
+pub fn play_back_input(
+    clock: Res<SimulationClock>,
+    mut input: ResMut<PlayerInput>,
+    playback: Option<Res<Playback>>,
+) {
+    if let Some(playback) = playback {
+        let recorded = playback
+            .inputs
+            .get(clock.steps as usize)
+            .copied()
+            .unwrap_or_default();
+        recorded.apply(&mut input);
+    }
+}
+
+/// Saves the run just ended, however it ended, over the last one's replay.
+pub fn save_recording(mut commands: Commands, recording: Option<Res<Recording>>) {
+    if let Some(recording) = recording {
+        let path = save_path(REPLAY_FILE);
+        recording.0.save(&path);
+        info!("Saved replay to {}", path.display());
+        commands.remove_resource::<Recording>();
+    }
+}
+
+pub fn stop_playback(mut commands: Commands) {
+    commands.remove_resource::<Playback>();
+}
+
+/// Space pauses, Up and Down change the speed, and Left and Right skip. Skipping back restarts
+/// the run and fast-forwards to the new spot, since a run can only be played forwards.
+pub fn playback_controls(
+    keys: Res<Input<KeyCode>>,
+    clock: Res<SimulationClock>,
+    playback: Option<ResMut<Playback>>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    if keys.just_pressed(KeyCode::Space) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::Up) {
+        playback.speed = (playback.speed + 1).min(SPEEDS.len() - 1);
+    }
+    if keys.just_pressed(KeyCode::Down) {
+        playback.speed = playback.speed.saturating_sub(1);
+    }
+
+    let now = duration(clock.steps as usize);
+    if keys.just_pressed(KeyCode::Right) {
+        playback.skip_to = Some(step_at(now + SKIP_SECONDS));
+    }
+    if keys.just_pressed(KeyCode::Left) {
+        playback.skip_to = Some(step_at(now - SKIP_SECONDS));
+        // Restarting exits and re-enters the game, regenerating the run from its seed.
+        let _ = app_state.restart();
+    }
+}
+
+/// Queues the replay's steps for this frame in place of the frame's own: as many as a skip
+/// still needs, or as many as the speed says are due. Agents are drawn between steps by how far
+/// through the next one the replay is, so the clock's overstep is the replay's too.
+pub fn queue_playback_steps(
+    time: Res<Time>,
+    mut clock: ResMut<SimulationClock>,
+    playback: Option<ResMut<Playback>>,
+    mut pending: ResMut<PendingSteps>,
+) {
+    let mut playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    let next = clock.steps as usize;
+    let remaining = playback.inputs.len().saturating_sub(next);
+    pending.0 = 0;
+
+    if let Some(target) = playback.skip_to {
+        // A skip back waits for the restart to bring the run back to the start.
+        if target >= next {
+            let steps = (target - next).min(remaining).min(MAX_STEPS_PER_FRAME);
+            pending.0 = steps as u32;
+            if steps == (target - next).min(remaining) {
+                playback.skip_to = None;
+            }
+        }
+        playback.owed = 0.0;
+        clock.overstep = 0.0;
+        return;
+    }
+
+    if !playback.paused {
+        playback.owed += time.delta_seconds() * playback.speed();
+        let due = (playback.owed / STEP) as usize;
+        playback.owed -= due as f32 * STEP;
+        if due >= remaining {
+            // Nothing more to owe once the recording runs out.
+            playback.owed = 0.0;
+        }
+        pending.0 = due.min(remaining).min(MAX_STEPS_PER_FRAME) as u32;
+    }
+    clock.overstep = playback.owed;
+}
+
+/// Shows where the replay is up to, across the top of the screen.
+#[derive(Component)]
+pub struct PlaybackLabel;
+
+pub fn spawn_playback_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    run_config: Res<RunConfig>,
+    playback: Option<Res<Playback>>,
+) {
+    // `start_recording` is ending any playback this isn't the replay for.
+    match playback {
+        Some(playback) if playback.replay.matches(&run_config) => {}
+        _ => return,
+    }
+    commands
+        .spawn_bundle(
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+                    font_size: 18.0,
+                    color: Color::WHITE,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    top: Val::Px(10.0),
+                    left: Val::Percent(40.0),
+                    ..default()
+                },
+                ..default()
+            }),
+        )
+        .insert(PlaybackLabel)
+        .insert(GameEntity);
+}
+
+fn minutes_and_seconds(seconds: f32) -> String {
+    let seconds = seconds.max(0.0) as u32;
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+pub fn update_playback_label(
+    clock: Res<SimulationClock>,
+    playback: Option<Res<Playback>>,
+    mut label_query: Query<&mut Text, With<PlaybackLabel>>,
+) {
+    let playback = match playback {
+        Some(playback) => playback,
+        None => return,
+    };
+    let mut value = format!(
+        "Replay {} / {}  {}x",
+        minutes_and_seconds(duration(clock.steps as usize)),
+        minutes_and_seconds(duration(playback.inputs.len())),
+        playback.speed()
+    );
+    if playback.skip_to.is_some() {
+        value.push_str("  Skipping");
+    } else if playback.paused {
+        value.push_str("  Paused");
+    }
+    for mut text in &mut label_query {
+        if text.sections[0].value != value {
+            text.sections[0].value = value.clone();
+        }
+    }
+}
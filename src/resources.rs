@@ -1,7 +1,104 @@
-struct Timer {
-    time_remaining: usize,
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::config::Difficulty;
+
+/// Seeded generator used for everything random in a run, so a seed reproduces the same city.
+pub struct GameRng(pub ChaCha8Rng);
+
+impl GameRng {
+    pub fn from_seed(seed: u64) -> Self {
+        GameRng(ChaCha8Rng::seed_from_u64(seed))
+    }
+}
+
+/// Numbers collected while a run is in progress, used for the score and the results screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunStats {
+    pub seed: u64,
+    pub time_taken: f32,
+    pub closest_approach: f32,
+    pub time_unseen: f32,
+    pub times_spotted: u32,
+    pub errands_completed: u32,
+    pub currently_seen: bool,
 }
 
-struct PopulationCounter {
-    people: usize,
+impl RunStats {
+    pub fn new(seed: u64) -> Self {
+        RunStats {
+            seed,
+            time_taken: 0.0,
+            closest_approach: f32::MAX,
+            time_unseen: 0.0,
+            times_spotted: 0,
+            errands_completed: 0,
+            currently_seen: false,
+        }
+    }
+
+    /// Errands are worth the most, then finishing quickly, keeping your distance and staying
+    /// out of sight. Getting spotted costs a flat penalty each time.
+    pub fn score(&self) -> u32 {
+        let errands = self.errands_completed as f32 * 1000.0;
+        let speed = (300.0 - self.time_taken).max(0.0) * 2.0;
+        let distance = self.closest_approach.min(600.0);
+        let unseen = if self.time_taken > 0.0 {
+            (self.time_unseen / self.time_taken) * 500.0
+        } else {
+            0.0
+        };
+        let spotted = self.times_spotted as f32 * 100.0;
+        (errands + speed + distance + unseen - spotted).max(0.0) as u32
+    }
+}
+
+/// How many of the run's sightings the spotted stinger has already played for. Set from the
+/// run's stats whenever a run starts or is continued, so only sightings after that play it.
+#[derive(Default)]
+pub struct SpottedStingers(pub u32);
+
+/// How sure the followers are that they're being led somewhere, from 0 to 1.
+#[derive(Default)]
+pub struct Suspicion(pub f32);
+
+/// How much sprint the player has left, from 0 to 1.
+pub struct Stamina(pub f32);
+
+impl Default for Stamina {
+    fn default() -> Self {
+        Stamina(1.0)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Errand {
+    pub building: String,
+    pub done: bool,
+}
+
+/// The places the player has to get to this run, in order.
+#[derive(Default)]
+pub struct Errands(pub Vec<Errand>);
+
+/// A hint for what the player can do right now, shown at the bottom of the HUD.
+#[derive(Default)]
+pub struct Prompt(pub Option<String>);
+
+/// Seed picked on the main menu; `None` rolls a fresh one for every run.
+#[derive(Default)]
+pub struct SelectedSeed(pub Option<u64>);
+
+/// Index into `MAPS` of the map picked on the main menu.
+#[derive(Default)]
+pub struct SelectedMap(pub usize);
+
+/// Everything needed to generate a run. Set when a run is started from the menu and left in
+/// place afterwards, so restarting or retrying just re-enters the game with it.
+#[derive(Default, Clone)]
+pub struct RunConfig {
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub map: usize,
 }
@@ -0,0 +1,195 @@
+use bevy::prelude::*;
+
+use crate::{
+    components::ResultsEntity,
+    config::GameConfig,
+    menu::{move_selection, take_press, SELECTED_COLOR},
+    resources::{RunConfig, RunStats},
+    AppState,
+};
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResultsItem {
+    RetrySameSeed,
+    NewSeed,
+    Menu,
+}
+
+impl ResultsItem {
+    pub const ALL: [ResultsItem; 3] = [
+        ResultsItem::RetrySameSeed,
+        ResultsItem::NewSeed,
+        ResultsItem::Menu,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ResultsItem::RetrySameSeed => "Retry Same Seed",
+            ResultsItem::NewSeed => "New Seed",
+            ResultsItem::Menu => "Menu",
+        }
+    }
+}
+
+/// Index into `ResultsItem::ALL` of the highlighted choice.
+#[derive(Default)]
+pub struct ResultsSelection(pub usize);
+
+pub fn win_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    stats: Res<RunStats>,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("win_splash.png"),
+            transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
+            ..default()
+        })
+        .insert(ResultsEntity);
+    spawn_run_summary(&mut commands, &asset_server, &config, &stats);
+}
+
+pub fn lose_setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    stats: Res<RunStats>,
+) {
+    commands
+        .spawn_bundle(SpriteBundle {
+            texture: asset_server.load("lose_splash.png"),
+            transform: Transform::from_xyz(0., 0., 0.).with_scale(Vec3::new(0.7, 0.7, 0.7)),
+            ..default()
+        })
+        .insert(ResultsEntity);
+    spawn_run_summary(&mut commands, &asset_server, &config, &stats);
+}
+
+/// Lays the stats breakdown for the finished run, and what to do next, over the win/lose
+/// splash art.
+fn spawn_run_summary(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    config: &GameConfig,
+    stats: &RunStats,
+) {
+    let style = TextStyle {
+        font: asset_server.load("fonts/Akira Expanded Demo.otf"),
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    let closest_approach = if stats.closest_approach == f32::MAX {
+        "-".to_string()
+    } else {
+        format!("{:.1}m", stats.closest_approach / config.pixels_per_meter)
+    };
+    let lines = [
+        format!("Score {}", stats.score()),
+        format!("Seed {}", stats.seed),
+        format!("Time {:.1}s", stats.time_taken),
+        format!("Closest Approach {}", closest_approach),
+        format!("Time Unseen {:.1}s", stats.time_unseen),
+        format!("Times Spotted {}", stats.times_spotted),
+        format!("Errands Completed {}", stats.errands_completed),
+    ];
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    bottom: Val::Px(20.0),
+                    right: Val::Px(20.0),
+                    ..default()
+                },
+                // UI flows bottom-up, so reverse the column to read top to bottom.
+                flex_direction: FlexDirection::ColumnReverse,
+                align_items: AlignItems::FlexEnd,
+                padding: UiRect::all(Val::Px(10.0)),
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+            ..default()
+        })
+        .insert(ResultsEntity)
+        .with_children(|parent| {
+            for line in lines {
+                parent.spawn_bundle(TextBundle::from_section(line, style.clone()));
+            }
+            for (index, item) in ResultsItem::ALL.into_iter().enumerate() {
+                let color = if index == 0 {
+                    SELECTED_COLOR
+                } else {
+                    Color::WHITE
+                };
+                parent
+                    .spawn_bundle(
+                        TextBundle::from_section(
+                            item.label(),
+                            TextStyle {
+                                font_size: 24.0,
+                                color,
+                                ..style.clone()
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect {
+                                top: Val::Px(if index == 0 { 12.0 } else { 2.0 }),
+                                ..default()
+                            },
+                            ..default()
+                        }),
+                    )
+                    .insert(item);
+            }
+        });
+    commands.insert_resource(ResultsSelection::default());
+}
+
+/// Retry replays the finished run's config exactly, new seed keeps its difficulty and map.
+pub fn results_navigation(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut selection: ResMut<ResultsSelection>,
+    mut run_config: ResMut<RunConfig>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    let mut pressed = |key: KeyCode, button: GamepadButtonType| {
+        take_press(&mut keys, &gamepads, &mut gamepad_buttons, key, button)
+    };
+    let item_count = ResultsItem::ALL.len();
+    move_selection(&mut pressed, &mut selection.0, item_count);
+    if !pressed(KeyCode::Return, GamepadButtonType::South) {
+        return;
+    }
+    match ResultsItem::ALL[selection.0] {
+        ResultsItem::RetrySameSeed => {
+            let _ = app_state.set(AppState::Game);
+        }
+        ResultsItem::NewSeed => {
+            run_config.seed = rand::random();
+            let _ = app_state.set(AppState::Game);
+        }
+        ResultsItem::Menu => {
+            let _ = app_state.set(AppState::Menu);
+        }
+    }
+}
+
+pub fn update_results_labels(
+    selection: Res<ResultsSelection>,
+    mut label_query: Query<(&ResultsItem, &mut Text)>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+    for (item, mut text) in label_query.iter_mut() {
+        text.sections[0].style.color = if ResultsItem::ALL[selection.0] == *item {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        };
+    }
+}
@@ -0,0 +1,308 @@
+//! Saving a run part way through and picking it up again later. A run is saved when it's left
+//! from the pause menu with Save And Quit, or when the window is closed mid-run, and can be
+//! continued from the main menu. A save can only be continued once.
+use std::fs;
+
+use bevy::{
+    app::AppExit,
+    ecs::system::{SystemParam, SystemState},
+    prelude::*,
+};
+use bevy_rapier2d::prelude::Velocity;
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::{Appearance, Bystander, Follower, Hidden, Player, Walker},
+    config::Difficulty,
+    gameplay::{AgentState, RunOver, SimulationClock},
+    generation::{building_named, Building, MAPS},
+    replay::Playback,
+    resources::{
+        Errand, Errands, GameRng, RunConfig, RunStats, SpottedStingers, Stamina, Suspicion,
+    },
+    storage::{load_ron, save_path, save_ron},
+    AppState,
+};
+
+pub const SAVE_FILE: &str = "saved_run.ron";
+/// Bump this when `SavedRun`'s layout changes. A save is only kept for one continue, so rather
+/// than being migrated, saves from other versions are dropped when loaded.
+const SAVED_RUN_VERSION: u32 = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedPlayer {
+    pub agent: AgentState,
+    /// Name of the building they're heading for.
+    pub destination: String,
+    pub hidden: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedBystander {
+    pub agent: AgentState,
+    pub destination: Vec3,
+    /// Name of the building they're heading for.
+    pub destination_building: String,
+    pub focus: f32,
+}
+
+impl SavedBystander {
+    /// The bystander, heading for a building on map `map`.
+    pub fn bystander(&self, map: usize) -> Bystander {
+        Bystander {
+            destination: self.destination,
+            destination_building: saved_building(map, &self.destination_building),
+            focus: self.focus,
+        }
+    }
+}
+
+/// Everything about a run in progress. The map's walls and buildings aren't saved, since
+/// they're the same every run.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedRun {
+    pub version: u32,
+    pub seed: u64,
+    pub difficulty: Difficulty,
+    pub map: usize,
+    /// Simulation steps taken so far.
+    pub steps: u64,
+    /// The run's generator, part way through, so the crowd and followers carry on as they
+    /// would have.
+    pub rng: ChaCha8Rng,
+    pub stats: RunStats,
+    pub suspicion: f32,
+    pub stamina: f32,
+    pub errands: Vec<Errand>,
+    pub player: SavedPlayer,
+    pub followers: Vec<AgentState>,
+    pub bystanders: Vec<SavedBystander>,
+}
+
+impl SavedRun {
+    /// The saved run, if there is one from this version on a map that still exists, whose
+    /// buildings are all still on it.
+    pub fn load() -> Option<Self> {
+        let saved: SavedRun = load_ron(&save_path(SAVE_FILE))?;
+        if saved.version != SAVED_RUN_VERSION {
+            warn!(
+                "Saved run is from version {}, not {}; it can't be continued",
+                saved.version, SAVED_RUN_VERSION
+            );
+            return None;
+        }
+        if saved.map >= MAPS.len() {
+            warn!("Saved run is on map {}, which doesn't exist", saved.map);
+            return None;
+        }
+        let buildings = std::iter::once(&saved.player.destination).chain(
+            saved
+                .bystanders
+                .iter()
+                .map(|bystander| &bystander.destination_building),
+        );
+        for name in buildings {
+            if building_named(saved.map, name).is_none() {
+                warn!("Saved run heads for {:?}, which isn't on the map", name);
+                return None;
+            }
+        }
+        Some(saved)
+    }
+
+    pub fn save(&self) {
+        let path = save_path(SAVE_FILE);
+        save_ron(&path, self);
+        info!("Saved run to {}", path.display());
+    }
+
+    pub fn delete() {
+        let path = save_path(SAVE_FILE);
+        if let Err(error) = fs::remove_file(&path) {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                warn!("Couldn't remove {}: {}", path.display(), error);
+            }
+        }
+    }
+
+    pub fn run_config(&self) -> RunConfig {
+        RunConfig {
+            seed: self.seed,
+            difficulty: self.difficulty,
+            map: self.map,
+        }
+    }
+
+    /// The run in progress in `world`, if there is one.
+    pub fn capture(world: &mut World) -> Option<Self> {
+        SystemState::<RunSnapshot>::new(world)
+            .get_mut(world)
+            .capture()
+    }
+}
+
+/// A saved run being continued. The spawn systems put its agents back instead of generating
+/// new ones, and `restore_run` then puts back the rest and removes it.
+pub struct ResumedRun(pub SavedRun);
+
+/// The saved run the main menu offers to continue.
+#[derive(Default)]
+pub struct ResumableRun(pub Option<SavedRun>);
+
+/// The building on map `map` a loaded save refers to, which `SavedRun::load` has made sure
+/// exists.
+pub fn saved_building(map: usize, name: &str) -> Building {
+    building_named(map, name).expect("saved runs are checked for unknown buildings when loaded")
+}
+
+/// Everything read to save a run in progress.
+#[derive(SystemParam)]
+#[allow(clippy::type_complexity)]
+pub struct RunSnapshot<'w, 's> {
+    run_config: Res<'w, RunConfig>,
+    run_over: Res<'w, RunOver>,
+    clock: Res<'w, SimulationClock>,
+    playback: Option<Res<'w, Playback>>,
+    // Only there once a run has started.
+    rng: Option<Res<'w, GameRng>>,
+    stats: Option<Res<'w, RunStats>>,
+    suspicion: Option<Res<'w, Suspicion>>,
+    stamina: Option<Res<'w, Stamina>>,
+    errands: Option<Res<'w, Errands>>,
+    player_query: Query<
+        'w,
+        's,
+        (
+            &'static Player,
+            &'static Transform,
+            &'static Velocity,
+            &'static Walker,
+            &'static Appearance,
+            Option<&'static Hidden>,
+        ),
+    >,
+    follower_query: Query<
+        'w,
+        's,
+        (
+            &'static Transform,
+            &'static Velocity,
+            &'static Walker,
+            &'static Appearance,
+        ),
+        With<Follower>,
+    >,
+    bystander_query: Query<
+        'w,
+        's,
+        (
+            &'static Bystander,
+            &'static Transform,
+            &'static Velocity,
+            &'static Walker,
+            &'static Appearance,
+        ),
+    >,
+}
+
+impl<'w, 's> RunSnapshot<'w, 's> {
+    /// The run as it is now, unless there's none in progress or it's already over. A replay
+    /// being watched is never captured, so it can't replace a run of the player's own.
+    pub fn capture(&self) -> Option<SavedRun> {
+        if self.run_over.0 || self.playback.is_some() {
+            return None;
+        }
+        let (player, transform, velocity, walker, appearance, hidden) =
+            self.player_query.get_single().ok()?;
+        Some(SavedRun {
+            version: SAVED_RUN_VERSION,
+            seed: self.run_config.seed,
+            difficulty: self.run_config.difficulty,
+            map: self.run_config.map,
+            steps: self.clock.steps,
+            rng: self.rng.as_ref()?.0.clone(),
+            stats: self.stats.as_deref()?.clone(),
+            suspicion: self.suspicion.as_ref()?.0,
+            stamina: self.stamina.as_ref()?.0,
+            errands: self.errands.as_ref()?.0.clone(),
+            player: SavedPlayer {
+                agent: AgentState::capture(transform, velocity, walker, appearance),
+                destination: player.destination.name.clone(),
+                hidden: hidden.is_some(),
+            },
+            followers: self
+                .follower_query
+                .iter()
+                .map(|(transform, velocity, walker, appearance)| {
+                    AgentState::capture(transform, velocity, walker, appearance)
+                })
+                .collect(),
+            bystanders: self
+                .bystander_query
+                .iter()
+                .map(
+                    |(bystander, transform, velocity, walker, appearance)| SavedBystander {
+                        agent: AgentState::capture(transform, velocity, walker, appearance),
+                        destination: bystander.destination,
+                        destination_building: bystander.destination_building.name.clone(),
+                        focus: bystander.focus,
+                    },
+                )
+                .collect(),
+        })
+    }
+}
+
+/// Saves the run on closing the game mid-run, and offers it on the main menu. Needs
+/// `DefaultPlugins`; putting a saved run back is part of `GameplayPlugin`.
+pub struct SavePlugin;
+
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ResumableRun>()
+            .add_system_set(SystemSet::on_enter(AppState::Menu).with_system(load_saved_run))
+            .add_system_to_stage(CoreStage::Last, save_on_exit);
+    }
+}
+
+pub fn load_saved_run(mut commands: Commands) {
+    commands.insert_resource(ResumableRun(SavedRun::load()));
+}
+
+/// Puts back what the spawn systems don't when a saved run is continued. Exclusive and at the
+/// end of entering the game, so it overrides what `start_run` set up for a new run.
+pub fn restore_run(world: &mut World) {
+    let saved = match world.remove_resource::<ResumedRun>() {
+        Some(ResumedRun(saved)) => saved,
+        None => return,
+    };
+    world.insert_resource(GameRng(saved.rng));
+    world.insert_resource(SpottedStingers(saved.stats.times_spotted));
+    world.insert_resource(saved.stats);
+    world.insert_resource(Suspicion(saved.suspicion));
+    world.insert_resource(Stamina(saved.stamina));
+    world.insert_resource(Errands(saved.errands));
+    world.insert_resource(SimulationClock {
+        steps: saved.steps,
+        ..default()
+    });
+}
+
+/// Closing the window mid-run, paused or not, saves it to continue later.
+pub fn save_on_exit(
+    mut exit: EventReader<AppExit>,
+    state: Res<State<AppState>>,
+    snapshot: RunSnapshot,
+) {
+    if exit.iter().next().is_none() {
+        return;
+    }
+    let in_run = *state.current() == AppState::Game || state.inactives().contains(&AppState::Game);
+    if !in_run {
+        return;
+    }
+    if let Some(saved) = snapshot.capture() {
+        saved.save();
+    }
+}
@@ -0,0 +1,285 @@
+use bevy::prelude::*;
+
+use crate::{
+    input::{Action, InputBindings},
+    menu::{move_selection, take_press, SELECTED_COLOR},
+    user_settings::{UserSettings, WindowModeSetting, RESOLUTIONS, TEXT_SCALES},
+    AppState,
+};
+
+/// Anything spawned for the settings screen, removed again when it's left.
+#[derive(Component)]
+pub struct SettingsEntity;
+
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingsItem {
+    WindowMode,
+    Resolution,
+    Vsync,
+    MasterVolume,
+    MusicVolume,
+    SfxVolume,
+    ColourBlind,
+    TextScale,
+    Binding(Action),
+    ResetControls,
+    Back,
+}
+
+impl SettingsItem {
+    pub fn all() -> Vec<SettingsItem> {
+        let mut items = vec![
+            SettingsItem::WindowMode,
+            SettingsItem::Resolution,
+            SettingsItem::Vsync,
+            SettingsItem::MasterVolume,
+            SettingsItem::MusicVolume,
+            SettingsItem::SfxVolume,
+            SettingsItem::ColourBlind,
+            SettingsItem::TextScale,
+        ];
+        items.extend(Action::ALL.into_iter().map(SettingsItem::Binding));
+        items.push(SettingsItem::ResetControls);
+        items.push(SettingsItem::Back);
+        items
+    }
+}
+
+/// Index into `SettingsItem::all()` of the highlighted row.
+#[derive(Default)]
+pub struct SettingsSelection(pub usize);
+
+/// The action waiting for its new key or button, if any.
+#[derive(Default)]
+pub struct Rebinding(pub Option<Action>);
+
+pub fn settings_setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let font = asset_server.load("fonts/Akira Expanded Demo.otf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 40.0,
+        color: Color::WHITE,
+    };
+    let row_style = TextStyle {
+        font,
+        font_size: 20.0,
+        color: Color::WHITE,
+    };
+    commands
+        .spawn_bundle(NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.0), Val::Percent(100.0)),
+                position_type: PositionType::Absolute,
+                // UI flows bottom-up, so reverse the column to read top to bottom.
+                flex_direction: FlexDirection::ColumnReverse,
+                justify_content: JustifyContent::Center,
+                align_items: AlignItems::Center,
+                ..default()
+            },
+            color: Color::rgba(0.0, 0.0, 0.0, 0.85).into(),
+            ..default()
+        })
+        .insert(SettingsEntity)
+        .with_children(|parent| {
+            parent.spawn_bundle(TextBundle::from_section("Settings", title_style));
+            for item in SettingsItem::all() {
+                // The label is filled in by `update_settings_labels`.
+                parent
+                    .spawn_bundle(TextBundle::from_section("", row_style.clone()))
+                    .insert(item);
+            }
+            parent.spawn_bundle(TextBundle::from_section(
+                "Left and right change a setting, enter rebinds a control",
+                TextStyle {
+                    font_size: 14.0,
+                    ..row_style
+                },
+            ));
+        });
+    commands.insert_resource(SettingsSelection::default());
+    commands.insert_resource(Rebinding::default());
+}
+
+/// Moves between rows, changes settings and rebinds controls. While an action is waiting to be
+/// rebound the next key or gamepad button pressed is taken as its new binding, or Esc cancels.
+#[allow(clippy::too_many_arguments)]
+pub fn settings_navigation(
+    mut keys: ResMut<Input<KeyCode>>,
+    gamepads: Res<Gamepads>,
+    mut gamepad_buttons: ResMut<Input<GamepadButton>>,
+    mut selection: ResMut<SettingsSelection>,
+    mut rebinding: ResMut<Rebinding>,
+    mut bindings: ResMut<InputBindings>,
+    mut settings: ResMut<UserSettings>,
+    mut app_state: ResMut<State<AppState>>,
+) {
+    if let Some(action) = rebinding.0 {
+        let key = keys.get_just_pressed().next().copied();
+        let button = gamepad_buttons.get_just_pressed().next().copied();
+        if keys.clear_just_pressed(KeyCode::Escape) {
+            rebinding.0 = None;
+        } else if let Some(key) = key {
+            keys.clear_just_pressed(key);
+            bindings.get_mut(action).keys = vec![key];
+            bindings.save();
+            rebinding.0 = None;
+        } else if let Some(button) = button {
+            gamepad_buttons.clear_just_pressed(button);
+            bindings.get_mut(action).button = Some(button.button_type);
+            bindings.save();
+            rebinding.0 = None;
+        }
+        return;
+    }
+
+    let mut pressed = |key: KeyCode, button: GamepadButtonType| {
+        take_press(&mut keys, &gamepads, &mut gamepad_buttons, key, button)
+    };
+    let items = SettingsItem::all();
+    move_selection(&mut pressed, &mut selection.0, items.len());
+    if pressed(KeyCode::Escape, GamepadButtonType::East) {
+        leave_settings(&mut app_state);
+        return;
+    }
+    let item = items[selection.0];
+    if pressed(KeyCode::Left, GamepadButtonType::DPadLeft) {
+        change_setting(item, false, &mut settings);
+    }
+    if pressed(KeyCode::Right, GamepadButtonType::DPadRight) {
+        change_setting(item, true, &mut settings);
+    }
+    if !pressed(KeyCode::Return, GamepadButtonType::South) {
+        return;
+    }
+    match item {
+        SettingsItem::Binding(action) => rebinding.0 = Some(action),
+        SettingsItem::ResetControls => {
+            *bindings = InputBindings::default();
+            bindings.save();
+        }
+        SettingsItem::Back => leave_settings(&mut app_state),
+        _ => change_setting(item, true, &mut settings),
+    }
+}
+
+/// Steps `current` through `options`, starting from the first if it isn't one of them.
+fn cycle<T: Copy + PartialEq>(options: &[T], current: T, forward: bool) -> T {
+    let count = options.len();
+    let next = match options.iter().position(|option| *option == current) {
+        Some(index) if forward => (index + 1) % count,
+        Some(index) => (index + count - 1) % count,
+        None => 0,
+    };
+    options[next]
+}
+
+fn step_volume(volume: f32, forward: bool) -> f32 {
+    let step = if forward { 0.1 } else { -0.1 };
+    // Round so repeated steps land exactly on tenths.
+    ((volume + step).clamp(0.0, 1.0) * 10.0).round() / 10.0
+}
+
+/// Changes a setting and saves it; rows that aren't settings are left alone.
+fn change_setting(item: SettingsItem, forward: bool, settings: &mut UserSettings) {
+    match item {
+        SettingsItem::WindowMode => {
+            settings.window_mode = cycle(&WindowModeSetting::ALL, settings.window_mode, forward)
+        }
+        SettingsItem::Resolution => {
+            settings.resolution = cycle(&RESOLUTIONS, settings.resolution, forward)
+        }
+        SettingsItem::Vsync => settings.vsync = !settings.vsync,
+        SettingsItem::MasterVolume => {
+            settings.master_volume = step_volume(settings.master_volume, forward)
+        }
+        SettingsItem::MusicVolume => {
+            settings.music_volume = step_volume(settings.music_volume, forward)
+        }
+        SettingsItem::SfxVolume => settings.sfx_volume = step_volume(settings.sfx_volume, forward),
+        SettingsItem::ColourBlind => settings.colour_blind = !settings.colour_blind,
+        SettingsItem::TextScale => {
+            settings.text_scale = cycle(&TEXT_SCALES, settings.text_scale, forward)
+        }
+        SettingsItem::Binding(_) | SettingsItem::ResetControls | SettingsItem::Back => return,
+    }
+    settings.save();
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+/// Back to whichever screen opened settings.
+fn leave_settings(app_state: &mut State<AppState>) {
+    if app_state.inactives().is_empty() {
+        let _ = app_state.set(AppState::Menu);
+    } else {
+        // Opened from the pause menu, which is still underneath.
+        let _ = app_state.pop();
+    }
+}
+
+fn settings_label(
+    item: SettingsItem,
+    settings: &UserSettings,
+    bindings: &InputBindings,
+    rebinding: &Rebinding,
+) -> String {
+    let percent = |volume: f32| (volume * 100.0).round() as u32;
+    match item {
+        SettingsItem::WindowMode => format!("< Window {} >", settings.window_mode.name()),
+        SettingsItem::Resolution => format!(
+            "< Resolution {}x{} >",
+            settings.resolution.0, settings.resolution.1
+        ),
+        SettingsItem::Vsync => format!("< Vsync {} >", on_off(settings.vsync)),
+        SettingsItem::MasterVolume => {
+            format!("< Master Volume {}% >", percent(settings.master_volume))
+        }
+        SettingsItem::MusicVolume => {
+            format!("< Music Volume {}% >", percent(settings.music_volume))
+        }
+        SettingsItem::SfxVolume => format!("< Effects Volume {}% >", percent(settings.sfx_volume)),
+        SettingsItem::ColourBlind => {
+            format!("< Colour Blind Palette {} >", on_off(settings.colour_blind))
+        }
+        SettingsItem::TextScale => format!("< Text Scale {}% >", percent(settings.text_scale)),
+        SettingsItem::Binding(action) if rebinding.0 == Some(action) => {
+            format!("{}  Press A Key Or Button", action.name())
+        }
+        SettingsItem::Binding(action) => {
+            format!("{}  {}", action.name(), bindings.get(action).describe())
+        }
+        SettingsItem::ResetControls => "Reset Controls".to_string(),
+        SettingsItem::Back => "Back".to_string(),
+    }
+}
+
+pub fn update_settings_labels(
+    selection: Res<SettingsSelection>,
+    rebinding: Res<Rebinding>,
+    bindings: Res<InputBindings>,
+    settings: Res<UserSettings>,
+    mut label_query: Query<(&SettingsItem, &mut Text)>,
+) {
+    if !(selection.is_changed()
+        || rebinding.is_changed()
+        || bindings.is_changed()
+        || settings.is_changed())
+    {
+        return;
+    }
+    let selected = SettingsItem::all()[selection.0];
+    for (item, mut text) in label_query.iter_mut() {
+        text.sections[0].value = settings_label(*item, &settings, &bindings, &rebinding);
+        text.sections[0].style.color = if *item == selected {
+            SELECTED_COLOR
+        } else {
+            Color::WHITE
+        };
+    }
+}
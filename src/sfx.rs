@@ -0,0 +1,286 @@
+use std::{collections::HashSet, f32::consts::FRAC_PI_4};
+
+use bevy::{audio::AudioSink, prelude::*};
+use bevy_rapier2d::prelude::*;
+
+use crate::{
+    audio::source_loaded,
+    components::{Bystander, Entrance, Follower, MainCamera, Walker},
+    config::GameConfig,
+    user_settings::UserSettings,
+};
+
+/// Each is two files, `_left.wav` and `_right.wav`; see `PannedSource`.
+const CROWD_PATH: &str = "audio/sfx/crowd";
+const FOOTSTEP_PATH: &str = "audio/sfx/footstep";
+const DOOR_PATH: &str = "audio/sfx/door";
+
+/// How many bystanders in earshot make the crowd murmur as loud as it gets.
+const FULL_CROWD: f32 = 40.0;
+/// Fraction of full volume the crowd murmur changes by per second, so it swells and fades
+/// rather than jumping as people come and go.
+const CROWD_FADE_RATE: f32 = 0.5;
+/// Pixels a follower walks between footsteps.
+const STRIDE: f32 = 32.0;
+
+/// How loud a sound at `source` is for a listener at `listener`, from 1 right next to it down
+/// to 0 at `range` and beyond.
+pub fn attenuation(listener: Vec2, source: Vec2, range: f32) -> f32 {
+    if range <= 0.0 {
+        return 0.0;
+    }
+    let falloff = (1.0 - listener.distance(source) / range).clamp(0.0, 1.0);
+    falloff * falloff
+}
+
+/// Left and right volumes for a sound `balance` of the way from the middle to one side, from -1
+/// fully left to 1 fully right. Constant power, so a sound doesn't dip as it crosses the middle.
+pub fn pan(balance: f32) -> (f32, f32) {
+    let angle = (balance.clamp(-1.0, 1.0) + 1.0) * FRAC_PI_4;
+    (angle.cos(), angle.sin())
+}
+
+/// Left and right volumes for a sound at `source`: quieter with distance as `attenuation` has
+/// it, and panned by how far it is to either side of the listener, fully so at `range`.
+pub fn placement(listener: Vec2, source: Vec2, range: f32) -> (f32, f32) {
+    let volume = attenuation(listener, source, range);
+    if volume <= 0.0 {
+        return (0.0, 0.0);
+    }
+    let (left, right) = pan((source.x - listener.x) / range);
+    (left * volume, right * volume)
+}
+
+/// A sound as two files, one with only a left channel and one with only a right. Bevy 0.8's
+/// audio can't pan, so they're played together at different volumes to place it.
+pub struct PannedSource {
+    left: Handle<AudioSource>,
+    right: Handle<AudioSource>,
+}
+
+impl PannedSource {
+    fn load(asset_server: &AssetServer, path: &str) -> Self {
+        PannedSource {
+            left: asset_server.load(&format!("{}_left.wav", path)),
+            right: asset_server.load(&format!("{}_right.wav", path)),
+        }
+    }
+
+    fn loaded(&self, asset_server: &AssetServer) -> bool {
+        source_loaded(asset_server, &self.left) && source_loaded(asset_server, &self.right)
+    }
+}
+
+/// Sounds are heard from the middle of the screen.
+fn listener_location(camera_query: &Query<&Transform, With<MainCamera>>) -> Vec2 {
+    camera_query.single().translation.truncate()
+}
+
+/// The looping crowd murmur, how loud it is from 0 to 1 before the volume settings are
+/// applied, and which side it's heard from, as `pan` takes it.
+pub struct Ambience {
+    source: PannedSource,
+    /// The left and right sinks, only there once the loop has loaded and started.
+    sinks: Option<(Handle<AudioSink>, Handle<AudioSink>)>,
+    level: f32,
+    target: f32,
+    balance: f32,
+    target_balance: f32,
+}
+
+/// The one-shot effects, loaded up front.
+pub struct SoundEffects {
+    footstep: PannedSource,
+    door: PannedSource,
+}
+
+/// Loads the effects and the crowd murmur at startup. The murmur starts silently once it has
+/// loaded; runs turn it up.
+pub fn load_sounds(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(SoundEffects {
+        footstep: PannedSource::load(&asset_server, FOOTSTEP_PATH),
+        door: PannedSource::load(&asset_server, DOOR_PATH),
+    });
+    commands.insert_resource(Ambience {
+        source: PannedSource::load(&asset_server, CROWD_PATH),
+        sinks: None,
+        level: 0.0,
+        target: 0.0,
+        balance: 0.0,
+        target_balance: 0.0,
+    });
+}
+
+pub fn start_ambience(
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    mut ambience: ResMut<Ambience>,
+) {
+    if ambience.sinks.is_some() || !ambience.source.loaded(&asset_server) {
+        return;
+    }
+    // Started on the same frame, the two sides stay together.
+    let start = |source: &Handle<AudioSource>| {
+        let sink =
+            audio.play_with_settings(source.clone(), PlaybackSettings::LOOP.with_volume(0.0));
+        // A strong handle keeps the sink around so its volume can be changed later.
+        audio_sinks.get_handle(sink)
+    };
+    let sinks = (start(&ambience.source.left), start(&ambience.source.right));
+    ambience.sinks = Some(sinks);
+}
+
+/// Sets the crowd murmur from how many bystanders are in earshot, nearer ones counting for more,
+/// and pans it towards the side most of them are on.
+pub fn track_crowd(
+    config: Res<GameConfig>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    bystander_query: Query<&Transform, With<Bystander>>,
+    mut ambience: ResMut<Ambience>,
+) {
+    let listener = listener_location(&camera_query);
+    let mut crowd = 0.0;
+    let mut offset = 0.0;
+    for transform in &bystander_query {
+        let location = transform.translation.truncate();
+        let volume = attenuation(listener, location, config.sound_range);
+        crowd += volume;
+        offset += volume * (location.x - listener.x);
+    }
+    ambience.target = (crowd / FULL_CROWD).min(1.0);
+    if crowd > 0.0 {
+        ambience.target_balance = offset / crowd / config.sound_range;
+    }
+}
+
+/// The streets go quiet when a run ends.
+pub fn quiet_ambience(mut ambience: ResMut<Ambience>) {
+    ambience.target = 0.0;
+}
+
+fn fade(value: f32, target: f32, step: f32) -> f32 {
+    if value < target {
+        (value + step).min(target)
+    } else {
+        (value - step).max(target)
+    }
+}
+
+/// Fades the crowd murmur towards its target volume and side, and applies the effects volume.
+pub fn update_ambience(
+    time: Res<Time>,
+    settings: Res<UserSettings>,
+    mut ambience: ResMut<Ambience>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    let step = CROWD_FADE_RATE * time.delta_seconds();
+    ambience.level = fade(ambience.level, ambience.target, step);
+    ambience.balance = fade(ambience.balance, ambience.target_balance, step);
+    // The sinks only exist once the loop has loaded and started playing.
+    let (left_sink, right_sink) = match &ambience.sinks {
+        Some(sinks) => sinks,
+        None => return,
+    };
+    let volume = ambience.level * settings.sfx_gain();
+    let (left, right) = pan(ambience.balance);
+    if let Some(sink) = audio_sinks.get(left_sink) {
+        sink.set_volume(left * volume);
+    }
+    if let Some(sink) = audio_sinks.get(right_sink) {
+        sink.set_volume(right * volume);
+    }
+}
+
+/// Plays both sides of `effect` at the volumes `placement` gives.
+fn play_effect(
+    asset_server: &AssetServer,
+    audio: &Audio,
+    settings: &UserSettings,
+    effect: &PannedSource,
+    (left, right): (f32, f32),
+) {
+    let gain = settings.sfx_gain();
+    if left + right <= 0.0 || gain <= 0.0 || !effect.loaded(asset_server) {
+        return;
+    }
+    for (source, volume) in [(&effect.left, left), (&effect.right, right)] {
+        audio.play_with_settings(
+            source.clone(),
+            PlaybackSettings::ONCE.with_volume(volume * gain),
+        );
+    }
+}
+
+/// How far a follower has walked since its last footstep.
+#[derive(Component, Default)]
+pub struct Footsteps {
+    distance: f32,
+}
+
+/// Plays a footstep every stride a follower walks. They carry as far as the follower hearing
+/// range, so an approaching follower is heard before it's seen.
+#[allow(clippy::too_many_arguments)]
+pub fn follower_footsteps(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<UserSettings>,
+    effects: Res<SoundEffects>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    mut follower_query: Query<(&Transform, &Walker, &mut Footsteps), With<Follower>>,
+) {
+    let listener = listener_location(&camera_query);
+    for (transform, walker, mut footsteps) in &mut follower_query {
+        footsteps.distance += walker.velocity.length() * time.delta_seconds();
+        if footsteps.distance < STRIDE {
+            continue;
+        }
+        footsteps.distance %= STRIDE;
+        let volumes = placement(
+            listener,
+            transform.translation.truncate(),
+            config.follower_hearing_range,
+        );
+        play_effect(&asset_server, &audio, &settings, &effects.footstep, volumes);
+    }
+}
+
+/// Plays a door sound whenever anyone steps into a shop entrance, once per entrance per frame
+/// so a crowd going in at once doesn't stack up.
+#[allow(clippy::too_many_arguments)]
+pub fn entrance_doors(
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    settings: Res<UserSettings>,
+    effects: Res<SoundEffects>,
+    mut collision_events: EventReader<CollisionEvent>,
+    camera_query: Query<&Transform, With<MainCamera>>,
+    entrance_query: Query<&Transform, With<Entrance>>,
+) {
+    let listener = listener_location(&camera_query);
+    let mut opened = HashSet::new();
+    for event in collision_events.iter() {
+        if let CollisionEvent::Started(first, second, _) = event {
+            opened.extend(
+                [*first, *second]
+                    .into_iter()
+                    .filter(|entity| entrance_query.contains(*entity)),
+            );
+        }
+    }
+    for entrance in opened {
+        let transform = match entrance_query.get(entrance) {
+            Ok(transform) => transform,
+            Err(_) => continue,
+        };
+        let volumes = placement(
+            listener,
+            transform.translation.truncate(),
+            config.sound_range,
+        );
+        play_effect(&asset_server, &audio, &settings, &effects.door, volumes);
+    }
+}
@@ -0,0 +1,65 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Where save files live: the platform data dir when there is one, otherwise next to the
+/// executable, and as a last resort the working directory.
+pub fn data_dir() -> PathBuf {
+    if let Some(dir) = dirs::data_dir() {
+        return dir.join("not_alone");
+    }
+    std::env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_path_buf))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+pub fn save_path(file_name: &str) -> PathBuf {
+    data_dir().join(file_name)
+}
+
+/// Reads a RON file. A missing file gives `None`; an unreadable or corrupt one is moved aside
+/// to `<name>.corrupt` so the next save doesn't silently destroy it, then also gives `None`.
+pub fn load_ron<T: DeserializeOwned>(path: &Path) -> Option<T> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(error) => {
+            warn!("Couldn't read {}: {}", path.display(), error);
+            return None;
+        }
+    };
+    match ron::from_str(&contents) {
+        Ok(value) => Some(value),
+        Err(error) => {
+            warn!("{} is corrupt, moving it aside: {}", path.display(), error);
+            let _ = fs::rename(path, path.with_extension("corrupt"));
+            None
+        }
+    }
+}
+
+/// Writes a RON file, going through a temporary file so a crash mid-write can't truncate it.
+pub fn save_ron<T: Serialize>(path: &Path, value: &T) {
+    let contents = match ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default()) {
+        Ok(contents) => contents,
+        Err(error) => {
+            warn!("Couldn't serialise {}: {}", path.display(), error);
+            return;
+        }
+    };
+    if let Some(parent) = path.parent() {
+        if let Err(error) = fs::create_dir_all(parent) {
+            warn!("Couldn't create {}: {}", parent.display(), error);
+            return;
+        }
+    }
+    let temp_path = path.with_extension("tmp");
+    if let Err(error) = fs::write(&temp_path, contents).and_then(|_| fs::rename(&temp_path, path)) {
+        warn!("Couldn't write {}: {}", path.display(), error);
+    }
+}
@@ -1,28 +1,29 @@
 use std::f32::consts::PI;
 
-use bevy::{ecs::query, prelude::*};
-use bevy_prototype_debug_lines::DebugLines;
-use bevy_rapier2d::{
-    prelude::{InteractionGroups, QueryFilter, RapierContext, Velocity},
-    rapier::{prelude::CollisionEvent, rayon::spawn},
-};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::{InteractionGroups, QueryFilter, RapierContext, Velocity};
 use rand::Rng;
 
 use crate::{
-    components::{Bystander, Entrance, Follower, Player, Spawner},
+    components::{Bystander, Entrance, Follower, Hidden, Player, Walker},
+    config::GameConfig,
+    gameplay::{RunOver, STEP},
+    input::{Action, InputBindings, PlayerInput},
+    movement::movement_intent,
+    resources::{Errands, GameRng, Prompt, RunConfig, RunStats, Stamina, Suspicion},
     AppState,
 };
 
 pub fn bystander_movement(
-    time: Res<Time>,
-
-    mut bystander_query: Query<(&mut Transform, &Bystander), With<Bystander>>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    mut bystander_query: Query<(&Transform, &Bystander, &mut Walker)>,
 ) {
-    let mut thread_rng = rand::thread_rng();
+    let rng = &mut rng.0;
 
-    for (mut transform, bystander) in &mut bystander_query {
-        let speed = thread_rng.gen_range(0.75..125.);
-        let direction = thread_rng.gen_range(0.0..360.0);
+    for (transform, bystander, mut walker) in &mut bystander_query {
+        let speed = rng.gen_range(config.bystander_speed_min..config.bystander_speed_max);
+        let direction = rng.gen_range(0.0..360.0);
         let destination = bystander.destination;
         let diff = destination - transform.translation;
         let heading = diff.y.atan2(diff.x) + (2. * PI);
@@ -30,146 +31,278 @@ pub fn bystander_movement(
         let smoothing_factor = bystander.focus;
         let actual_direction = ((heading * smoothing_factor) + direction) / (1. + smoothing_factor);
         let direction_in_radians = actual_direction.to_radians();
-        let movement_y = speed * time.delta_seconds() * direction_in_radians.sin();
-        let movement_x = speed * time.delta_seconds() * direction_in_radians.cos();
-        transform.translation.y += movement_y;
-        transform.translation.x += movement_x;
+        walker.target_velocity =
+            Vec2::new(direction_in_radians.cos(), direction_in_radians.sin()) * speed;
     }
 }
 
-pub fn camera_tracker(
-    mut camera_query: Query<&mut Transform, With<Camera>>,
-    player_query: Query<&mut Transform, (With<Player>, Without<Camera>)>,
-) {
-    let mut camera = camera_query.single_mut();
-    let player = player_query.single();
-    camera.translation = player.translation.clone();
+/// Despawns everything tagged with the state's marker component, children included. Registered
+/// on exit of each state with that state's marker.
+pub fn despawn_with<T: Component>(mut commands: Commands, query: Query<Entity, With<T>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
 }
 
 pub fn follower_system(
-    time: Res<Time>,
-
-    mut follower_query: Query<(&mut Transform, &mut Velocity), With<Follower>>,
+    mut rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    mut follower_query: Query<(&Transform, &mut Velocity, &mut Walker), With<Follower>>,
     player_query: Query<&mut Transform, (With<Player>, Without<Follower>)>,
-    mut lines: ResMut<DebugLines>,
 ) {
-    let mut thread_rng = rand::thread_rng();
-
-    let (mut follower, mut velocity) = follower_query.single_mut();
+    let rng = &mut rng.0;
+    let settings = config.difficulty(run_config.difficulty);
     let player = player_query.single();
-    let diff = follower.translation - player.translation;
-    let heading = diff.y.atan2(diff.x) + (2. * PI);
-
-    let speed = 100.0;
-    let direction = thread_rng.gen_range(0.0..(2.0 * PI));
-    let smoothing_factor = 2.5;
-    let actual_direction = ((heading * smoothing_factor) + direction) / (1. + smoothing_factor);
-    let movement_y = speed * time.delta_seconds() * actual_direction.sin();
-    let movement_x = speed * time.delta_seconds() * actual_direction.cos();
-    // println!(
-    //     "Angle {}, X {}, Y {}",
-    //     heading.to_degrees(),
-    //     movement_x,
-    //     movement_y
-    // );
-    follower.translation.y -= movement_y;
-    follower.translation.x -= movement_x;
-    velocity.linvel = velocity.linvel * 0.95;
-    velocity.angvel = 0.0;
-    //Follower heading line
-    // lines.line(
-    //     follower.translation,
-    //     follower.translation + (Vec3::new(movement_x, movement_y, 0.) * 10.0),
-    //     0.0,
-    // );
-    // //Line from follower to player
-    // lines.line(player.translation, follower.translation, 0.0);
+
+    for (follower, mut velocity, mut walker) in &mut follower_query {
+        let diff = follower.translation - player.translation;
+        let heading = diff.y.atan2(diff.x) + (2. * PI);
+
+        let speed = settings.follower_speed;
+        let direction = rng.gen_range(0.0..(2.0 * PI));
+        let smoothing_factor = settings.follower_smoothing;
+        let actual_direction = ((heading * smoothing_factor) + direction) / (1. + smoothing_factor);
+        // `diff` points from the player to the follower, so head the opposite way.
+        walker.target_velocity = -Vec2::new(actual_direction.cos(), actual_direction.sin()) * speed;
+        velocity.linvel *= config.velocity_damping;
+        velocity.angvel = 0.0;
+    }
 }
 
-/// The sprite is animated by changing its translation depending on the time that has passed since
-/// the last frame.
+/// Steers the player from this frame's input; `movement::walk` does the actual moving.
+/// Sprinting uses up stamina, which comes back while walking or standing still.
 pub fn sprite_movement(
-    time: Res<Time>,
-    keys: Res<Input<KeyCode>>,
-    mut sprite_position: Query<(&mut Transform, &mut Velocity), With<Player>>,
+    input: Res<PlayerInput>,
+    config: Res<GameConfig>,
+    mut stamina: ResMut<Stamina>,
+    mut sprite_position: Query<(&mut Velocity, &mut Walker, Option<&Hidden>), With<Player>>,
 ) {
-    let speed = 100.0;
-    for (mut transform, mut velocity) in &mut sprite_position {
-        if keys.pressed(KeyCode::Up) {
-            transform.translation.y += speed * time.delta_seconds();
-        }
-        if keys.pressed(KeyCode::Down) {
-            transform.translation.y -= speed * time.delta_seconds();
-        }
-        if keys.pressed(KeyCode::Left) {
-            transform.translation.x -= speed * time.delta_seconds();
-        }
-        if keys.pressed(KeyCode::Right) {
-            transform.translation.x += speed * time.delta_seconds();
-        }
-        velocity.linvel = velocity.linvel * 0.95;
-        velocity.angvel = 0.0;
-        // println!("({},{})", transform.translation.x, transform.translation.y);
+    let (mut velocity, mut walker, hidden) = sprite_position.single_mut();
+    let intent = if hidden.is_some() {
+        Vec2::ZERO
+    } else {
+        movement_intent(input.movement)
+    };
+    let sprinting = input.sprint && stamina.0 > 0.0 && intent != Vec2::ZERO;
+
+    let mut speed = config.player_speed;
+    if sprinting {
+        speed *= config.sprint_multiplier;
+    } else if input.sneak {
+        speed *= config.sneak_multiplier;
+    }
+    walker.target_velocity = intent * speed;
+    velocity.linvel *= config.velocity_damping;
+    velocity.angvel = 0.0;
+
+    let rate = if sprinting {
+        -config.stamina_drain_rate
+    } else {
+        config.stamina_regen_rate
+    };
+    let remaining = (stamina.0 + rate * STEP).clamp(0.0, 1.0);
+    // Only write when it moves, so the HUD can tell when the bar needs redrawing.
+    if remaining != stamina.0 {
+        stamina.0 = remaining;
     }
 }
 
-fn handle_bystanders_arriving_at_destination(
+/// Lets the player duck into any doorway but their destination's to hide from the followers,
+/// and step back out again, and offers the prompt for whichever applies.
+pub fn player_hiding(
+    mut commands: Commands,
+    input: Res<PlayerInput>,
+    bindings: Res<InputBindings>,
     rapier_context: Res<RapierContext>,
     query_entrances: Query<(Entity, &Entrance)>,
-    query_bystanders: Query<(Entity, &Bystander)>,
-    mut spawner_query: Query<&mut Spawner>,
-    mut commands: Commands,
+    mut query_player: Query<(Entity, &Player, &mut Visibility, Option<&Hidden>)>,
+    mut prompt: ResMut<Prompt>,
 ) {
-    let mut spawner = spawner_query.single_mut();
-    for (entrance, entrance_component) in query_entrances.iter() {
-        for (bystander, bystander_component) in query_bystanders.iter() {
-            if rapier_context.intersection_pair(entrance, bystander) == Some(true) {
-                if entrance_component.building_name == bystander_component.destination_building.name
-                {
-                    commands.entity(bystander).despawn();
-                    spawner.current_count -= 1;
-                }
-            }
+    let (player, player_component, mut visibility, hidden) = query_player.single_mut();
+    let in_doorway = query_entrances
+        .iter()
+        .any(|(entrance, entrance_component)| {
+            entrance_component.building_name != player_component.destination.name
+                && rapier_context.intersection_pair(entrance, player) == Some(true)
+        });
+
+    let hiding = hidden.is_some();
+    let toggle = input.interact && (hiding || in_doorway);
+    let now_hiding = hiding != toggle;
+    if toggle {
+        if now_hiding {
+            commands.entity(player).insert(Hidden);
+        } else {
+            commands.entity(player).remove::<Hidden>();
         }
+        visibility.is_visible = !now_hiding;
+    }
+
+    let key = bindings.get(Action::Interact).primary_name();
+    let text = if now_hiding {
+        Some(format!("Press {} To Stop Hiding", key))
+    } else if in_doorway {
+        Some(format!("Press {} To Hide", key))
+    } else {
+        None
+    };
+    if prompt.0 != text {
+        prompt.0 = text;
+    }
+}
+
+/// Casts a ray from the follower towards the player, ignoring bystanders, and reports whether the
+/// first thing it hits within `vision_range` is the player.
+pub fn follower_can_see_player(
+    rapier_context: &RapierContext,
+    follower_location: Vec2,
+    player_location: Vec2,
+    player: Entity,
+    vision_range: f32,
+) -> bool {
+    let ray_direction = (player_location - follower_location).normalize();
+    // Start outside the follower's own collider.
+    let ray_origin = follower_location + (ray_direction * 24.0);
+
+    let query_filter = QueryFilter::default()
+        .exclude_sensors()
+        .groups(InteractionGroups::new(0b10, 0b10));
+    if let Some((entity, _)) =
+        rapier_context.cast_ray(ray_origin, ray_direction, vision_range, true, query_filter)
+    {
+        entity == player
+    } else {
+        false
+    }
+}
+
+/// Whether any follower currently has line of sight to the player.
+fn player_is_seen(
+    rapier_context: &RapierContext,
+    player: Entity,
+    player_location: Vec2,
+    query_follower: &Query<&Transform, With<Follower>>,
+    vision_range: f32,
+) -> bool {
+    query_follower.iter().any(|follower_transform| {
+        follower_can_see_player(
+            rapier_context,
+            follower_transform.translation.truncate(),
+            player_location,
+            player,
+            vision_range,
+        )
+    })
+}
+
+pub fn track_run_stats(
+    rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    mut stats: ResMut<RunStats>,
+    query_player: Query<(Entity, &Transform, Option<&Hidden>), With<Player>>,
+    query_follower: Query<&Transform, With<Follower>>,
+) {
+    let (player, player_transform, hidden) = query_player.single();
+    let player_location = player_transform.translation.truncate();
+    let vision_range = config
+        .difficulty(run_config.difficulty)
+        .follower_vision_range;
+
+    stats.time_taken += STEP;
+    for follower_transform in &query_follower {
+        stats.closest_approach = stats
+            .closest_approach
+            .min(player_location.distance(follower_transform.translation.truncate()));
+    }
+
+    let seen = hidden.is_none()
+        && player_is_seen(
+            &rapier_context,
+            player,
+            player_location,
+            &query_follower,
+            vision_range,
+        );
+    if seen && !stats.currently_seen {
+        stats.times_spotted += 1;
+    }
+    if !seen {
+        stats.time_unseen += STEP;
+    }
+    stats.currently_seen = seen;
+}
+
+/// Fills the suspicion meter while the player is in sight and ends the run when it tops out or
+/// the time limit passes.
+pub fn enforce_run_limits(
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
+    stats: Res<RunStats>,
+    mut suspicion: ResMut<Suspicion>,
+    mut app_state: ResMut<State<AppState>>,
+    mut run_over: ResMut<RunOver>,
+) {
+    let settings = config.difficulty(run_config.difficulty);
+    let rate = if stats.currently_seen {
+        settings.suspicion_fill_rate
+    } else {
+        -settings.suspicion_drain_rate
+    };
+    let level = (suspicion.0 + rate * STEP).clamp(0.0, 1.0);
+    // Only write when it moves, so the HUD can tell when the meter needs redrawing.
+    if level != suspicion.0 {
+        suspicion.0 = level;
+    }
+
+    if suspicion.0 >= 1.0 || stats.time_taken >= settings.time_limit {
+        // The arrival check may have already ended the run this step.
+        let _ = app_state.set(AppState::Lose);
+        run_over.0 = true;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_player_arrival_at_destination(
     rapier_context: Res<RapierContext>,
+    config: Res<GameConfig>,
+    run_config: Res<RunConfig>,
     query_entrances: Query<(Entity, &Entrance)>,
     query_player: Query<(Entity, &Player, &Transform)>,
-    query_follower: Query<(Entity, &Follower, &Transform)>,
+    query_follower: Query<&Transform, With<Follower>>,
+    mut stats: ResMut<RunStats>,
+    mut errands: ResMut<Errands>,
     mut app_state: ResMut<State<AppState>>,
+    mut run_over: ResMut<RunOver>,
 ) {
     let (player, player_component, player_transform) = query_player.single();
-    let (follower, follower_component, follower_transform) = query_follower.single();
+    let vision_range = config
+        .difficulty(run_config.difficulty)
+        .follower_vision_range;
     for (entrance, entrance_component) in query_entrances.iter() {
-        if rapier_context.intersection_pair(entrance, player) == Some(true) {
-            if entrance_component.building_name == player_component.destination.name {
-                println!("Player arrived at destination");
-                // Check if player can be seen by the follower
-                let follower_center = follower_transform.translation.truncate();
-                let player_location = player_transform.translation.truncate();
-                let ray_direction = (player_location - follower_center).normalize() * 3.0;
-                let ray_origin = follower_center + (ray_direction * 8.0);
-
-                let query_filter = QueryFilter::default()
-                    .exclude_sensors()
-                    .groups(InteractionGroups::new(0b10, 0b10));
-                if let Some((entity, _)) =
-                    rapier_context.cast_ray(ray_origin, ray_direction, 200.0, true, query_filter)
-                {
-                    println!(
-                        "Entity {:?} hit at point, player == {:?}, follower == {:?}",
-                        entity, player, follower
-                    );
-                    if entity == player {
-                        app_state.set(AppState::Lose).unwrap();
-                    } else {
-                        app_state.set(AppState::Win).unwrap();
+        if rapier_context.intersection_pair(entrance, player) == Some(true)
+            && entrance_component.building_name == player_component.destination.name
+        {
+            info!("Player arrived at destination");
+            // Check if player can be seen by a follower
+            let player_location = player_transform.translation.truncate();
+            let seen = player_is_seen(
+                &rapier_context,
+                player,
+                player_location,
+                &query_follower,
+                vision_range,
+            );
+            run_over.0 = true;
+            if seen {
+                let _ = app_state.set(AppState::Lose);
+            } else if app_state.set(AppState::Win).is_ok() {
+                stats.errands_completed += 1;
+                for errand in errands.0.iter_mut() {
+                    if errand.building == entrance_component.building_name {
+                        errand.done = true;
                     }
-                } else {
-                    app_state.set(AppState::Win).unwrap();
                 }
             }
         }
@@ -0,0 +1,174 @@
+use bevy::{input::InputSystem, prelude::*};
+use bevy_prototype_debug_lines::DebugLinesPlugin;
+use bevy_prototype_lyon::prelude::*;
+
+use crate::{
+    camera::{camera_follow, camera_zoom, reset_camera, spawn_camera},
+    components::ResultsEntity,
+    config::Difficulty,
+    gameplay::{in_game, GameplayStage},
+    high_scores::{
+        high_scores_back_to_menu, high_scores_setup, load_high_scores, record_loss, record_win,
+        HighScoresEntity,
+    },
+    hud::{
+        spawn_hud, update_hud_objective, update_hud_prompt, update_hud_stamina,
+        update_hud_suspicion, update_hud_timer,
+    },
+    indicators::{spawn_indicators, update_indicators},
+    input::read_player_input,
+    menu::{
+        main_menu_mouse, main_menu_navigation, main_menu_seed_entry, main_menu_setup,
+        update_menu_labels, MenuEntity,
+    },
+    minimap::{spawn_minimap, update_minimap},
+    pause::{
+        game_pause_input, pause_menu_navigation, pause_menu_setup, pause_physics, resume_physics,
+        update_pause_labels, PauseEntity,
+    },
+    resources::{SelectedMap, SelectedSeed},
+    results::{lose_setup, results_navigation, update_results_labels, win_setup},
+    settings::{settings_navigation, settings_setup, update_settings_labels, SettingsEntity},
+    systems::despawn_with,
+    user_settings::{
+        apply_shape_highlights, apply_text_scale, apply_ui_highlights, apply_window_settings,
+    },
+    AppState,
+};
+
+/// Everything the person playing sees and touches: reading their input, the camera, the menus
+/// and results screens, and the in-game HUD, minimap and arrows. Needs `DefaultPlugins`.
+pub struct UiPlugin;
+
+impl Plugin for UiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Difficulty>()
+            .init_resource::<SelectedMap>()
+            .init_resource::<SelectedSeed>()
+            .add_plugin(ShapePlugin)
+            .add_plugin(DebugLinesPlugin::default())
+            .add_system_to_stage(CoreStage::PreUpdate, read_player_input.after(InputSystem))
+            .add_system(apply_window_settings)
+            .add_system(apply_text_scale)
+            .add_system(apply_shape_highlights)
+            .add_system(apply_ui_highlights)
+            .add_startup_system(spawn_camera)
+            //Main Screen Systems
+            .add_system_set(
+                SystemSet::on_enter(AppState::Menu)
+                    .with_system(main_menu_setup)
+                    .with_system(reset_camera)
+                    .with_system(load_high_scores),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Menu)
+                    .with_system(main_menu_navigation)
+                    .with_system(main_menu_mouse)
+                    .with_system(main_menu_seed_entry)
+                    .with_system(update_menu_labels),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Menu).with_system(despawn_with::<MenuEntity>),
+            )
+            // Game Systems
+            .add_system_set(
+                SystemSet::on_enter(AppState::Game)
+                    .with_system(spawn_indicators)
+                    .with_system(spawn_hud),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Game)
+                    .with_system(spawn_minimap)
+                    .with_system(game_pause_input),
+            )
+            // The camera, map, arrows and HUD show where the run ended up this frame.
+            .add_system_set_to_stage(
+                GameplayStage::AfterSimulation,
+                SystemSet::new()
+                    .with_run_criteria(in_game)
+                    .with_system(camera_zoom)
+                    .with_system(camera_follow.after(camera_zoom))
+                    .with_system(update_minimap.after(camera_follow))
+                    .with_system(update_indicators.after(camera_follow))
+                    .with_system(update_hud_objective)
+                    .with_system(update_hud_timer)
+                    .with_system(update_hud_suspicion)
+                    .with_system(update_hud_stamina)
+                    .with_system(update_hud_prompt),
+            )
+            // Pause Systems
+            .add_system_set(
+                SystemSet::on_enter(AppState::Paused)
+                    .with_system(pause_menu_setup)
+                    .with_system(pause_physics),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Paused)
+                    .with_system(pause_menu_navigation)
+                    .with_system(update_pause_labels),
+            )
+            // Settings can be opened on top of the pause menu, so hide it meanwhile.
+            .add_system_set(
+                SystemSet::on_pause(AppState::Paused).with_system(despawn_with::<PauseEntity>),
+            )
+            .add_system_set(SystemSet::on_resume(AppState::Paused).with_system(pause_menu_setup))
+            .add_system_set(
+                SystemSet::on_exit(AppState::Paused)
+                    .with_system(despawn_with::<PauseEntity>)
+                    .with_system(resume_physics),
+            )
+            // Lose System
+            .add_system_set(
+                SystemSet::on_exit(AppState::Lose).with_system(despawn_with::<ResultsEntity>),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Lose)
+                    .with_system(results_navigation)
+                    .with_system(update_results_labels),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Lose)
+                    .with_system(lose_setup)
+                    .with_system(reset_camera)
+                    .with_system(record_loss),
+            )
+            // Win System
+            .add_system_set(
+                SystemSet::on_exit(AppState::Win).with_system(despawn_with::<ResultsEntity>),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::Win)
+                    .with_system(results_navigation)
+                    .with_system(update_results_labels),
+            )
+            .add_system_set(
+                SystemSet::on_enter(AppState::Win)
+                    .with_system(win_setup)
+                    .with_system(reset_camera)
+                    .with_system(record_win),
+            )
+            // High Score Systems
+            .add_system_set(
+                SystemSet::on_enter(AppState::HighScores)
+                    .with_system(high_scores_setup)
+                    .with_system(reset_camera),
+            )
+            .add_system_set(
+                SystemSet::on_update(AppState::HighScores).with_system(high_scores_back_to_menu),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::HighScores)
+                    .with_system(despawn_with::<HighScoresEntity>),
+            )
+            // Settings Systems
+            .add_system_set(SystemSet::on_enter(AppState::Settings).with_system(settings_setup))
+            .add_system_set(
+                SystemSet::on_update(AppState::Settings)
+                    .with_system(settings_navigation)
+                    .with_system(update_settings_labels),
+            )
+            .add_system_set(
+                SystemSet::on_exit(AppState::Settings).with_system(despawn_with::<SettingsEntity>),
+            );
+    }
+}
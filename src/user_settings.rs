@@ -0,0 +1,289 @@
+use bevy::{
+    prelude::*,
+    window::{PresentMode, WindowMode},
+};
+use bevy_prototype_lyon::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    components::Follower,
+    storage::{load_ron, save_path, save_ron},
+};
+
+pub const SETTINGS_FILE: &str = "settings.ron";
+
+pub const RESOLUTIONS: [(f32, f32); 4] = [
+    (1280.0, 720.0),
+    (1600.0, 900.0),
+    (1920.0, 1080.0),
+    (2560.0, 1440.0),
+];
+
+pub const TEXT_SCALES: [f32; 4] = [0.75, 1.0, 1.25, 1.5];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WindowModeSetting {
+    Windowed,
+    Borderless,
+    Fullscreen,
+}
+
+impl WindowModeSetting {
+    pub const ALL: [WindowModeSetting; 3] = [
+        WindowModeSetting::Windowed,
+        WindowModeSetting::Borderless,
+        WindowModeSetting::Fullscreen,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            WindowModeSetting::Windowed => "Windowed",
+            WindowModeSetting::Borderless => "Borderless",
+            WindowModeSetting::Fullscreen => "Fullscreen",
+        }
+    }
+
+    fn window_mode(&self) -> WindowMode {
+        match self {
+            WindowModeSetting::Windowed => WindowMode::Windowed,
+            WindowModeSetting::Borderless => WindowMode::BorderlessFullscreen,
+            WindowModeSetting::Fullscreen => WindowMode::Fullscreen,
+        }
+    }
+}
+
+/// Colours that tell the player and followers apart.
+pub struct Palette {
+    pub player: Color,
+    pub follower: Color,
+}
+
+/// The player's own preferences, as opposed to the gameplay tuning in `GameConfig`. Saved to
+/// `settings.ron` whenever they're changed on the settings screen.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UserSettings {
+    pub window_mode: WindowModeSetting,
+    pub resolution: (f32, f32),
+    pub vsync: bool,
+    /// Volumes from 0 to 1. Music and effects are both scaled by the master volume.
+    pub master_volume: f32,
+    pub music_volume: f32,
+    pub sfx_volume: f32,
+    /// Swaps the player and follower colours for a blue/orange pair that stays distinct with
+    /// the common kinds of colour blindness.
+    pub colour_blind: bool,
+    pub text_scale: f32,
+}
+
+impl Default for UserSettings {
+    fn default() -> Self {
+        UserSettings {
+            window_mode: WindowModeSetting::Windowed,
+            resolution: RESOLUTIONS[0],
+            vsync: true,
+            master_volume: 1.0,
+            music_volume: 0.7,
+            sfx_volume: 0.8,
+            colour_blind: false,
+            text_scale: 1.0,
+        }
+    }
+}
+
+impl UserSettings {
+    pub fn load() -> Self {
+        load_ron(&save_path(SETTINGS_FILE)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        save_ron(&save_path(SETTINGS_FILE), self);
+    }
+
+    /// Inserted before `DefaultPlugins` so the window is created with these settings rather
+    /// than resized after it opens.
+    pub fn window_descriptor(&self) -> WindowDescriptor {
+        WindowDescriptor {
+            title: "Not Alone".to_string(),
+            width: self.resolution.0,
+            height: self.resolution.1,
+            mode: self.window_mode.window_mode(),
+            present_mode: self.present_mode(),
+            ..default()
+        }
+    }
+
+    fn present_mode(&self) -> PresentMode {
+        if self.vsync {
+            PresentMode::Fifo
+        } else {
+            PresentMode::Immediate
+        }
+    }
+
+    pub fn palette(&self) -> Palette {
+        if self.colour_blind {
+            Palette {
+                player: Color::rgb(0.0, 0.45, 0.7),
+                follower: Color::rgb(0.9, 0.6, 0.0),
+            }
+        } else {
+            Palette {
+                player: Color::CYAN,
+                follower: Color::rgb(0.9, 0.2, 0.2),
+            }
+        }
+    }
+
+    pub fn music_gain(&self) -> f32 {
+        self.master_volume * self.music_volume
+    }
+
+    pub fn sfx_gain(&self) -> f32 {
+        self.master_volume * self.sfx_volume
+    }
+}
+
+/// Applies window changes made on the settings screen to the open window.
+pub fn apply_window_settings(settings: Res<UserSettings>, mut windows: ResMut<Windows>) {
+    // The window was already created with the settings it started with.
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_mode(settings.window_mode.window_mode());
+        window.set_resolution(settings.resolution.0, settings.resolution.1);
+        window.set_present_mode(settings.present_mode());
+    }
+}
+
+/// Records the text scale already applied to a piece of text, so a new scale can be applied
+/// relative to it.
+#[derive(Component)]
+pub struct ScaledText(f32);
+
+/// Scales every piece of text by the text scale setting when the setting changes, and new
+/// text as it's spawned.
+pub fn apply_text_scale(
+    mut commands: Commands,
+    settings: Res<UserSettings>,
+    mut text_query: Query<(Entity, &mut Text, Option<&mut ScaledText>)>,
+    added_query: Query<Entity, Added<Text>>,
+) {
+    if settings.is_changed() {
+        for (entity, text, scaled) in &mut text_query {
+            scale_text(&mut commands, settings.text_scale, entity, text, scaled);
+        }
+        return;
+    }
+    for entity in &added_query {
+        if let Ok((entity, text, scaled)) = text_query.get_mut(entity) {
+            scale_text(&mut commands, settings.text_scale, entity, text, scaled);
+        }
+    }
+}
+
+fn scale_text(
+    commands: &mut Commands,
+    text_scale: f32,
+    entity: Entity,
+    mut text: Mut<Text>,
+    scaled: Option<Mut<ScaledText>>,
+) {
+    let applied = scaled.as_ref().map_or(1.0, |scaled| scaled.0);
+    if applied == text_scale {
+        return;
+    }
+    for section in text.sections.iter_mut() {
+        section.style.font_size *= text_scale / applied;
+    }
+    match scaled {
+        Some(mut scaled) => scaled.0 = text_scale,
+        None => {
+            commands.entity(entity).insert(ScaledText(text_scale));
+        }
+    }
+}
+
+/// Which palette colour an entity shows.
+#[derive(Component, Clone, Copy, PartialEq, Eq)]
+pub enum Highlight {
+    Player,
+    Follower,
+}
+
+impl Highlight {
+    fn color(&self, palette: &Palette) -> Color {
+        match self {
+            Highlight::Player => palette.player,
+            Highlight::Follower => palette.follower,
+        }
+    }
+}
+
+/// The colour a highlighted shape was spawned with, put back when the colour-blind palette is
+/// turned off.
+#[derive(Component)]
+pub struct OriginalColor(Color);
+
+/// Colours highlighted shapes from the colour-blind palette while it's on: the player is
+/// filled with its colour, followers are outlined in theirs, and anything else (like the
+/// indicator arrows) is filled. With it off they keep the colours they were spawned with.
+#[allow(clippy::type_complexity)]
+pub fn apply_shape_highlights(
+    mut commands: Commands,
+    settings: Res<UserSettings>,
+    mut shape_query: Query<(
+        Entity,
+        &Highlight,
+        &mut DrawMode,
+        Option<&Follower>,
+        Option<&OriginalColor>,
+    )>,
+    added_query: Query<(), Added<Highlight>>,
+) {
+    if !settings.is_changed() && added_query.is_empty() {
+        return;
+    }
+    let palette = settings.palette();
+    for (entity, highlight, mut draw_mode, follower, original) in &mut shape_query {
+        if let DrawMode::Outlined {
+            fill_mode,
+            outline_mode,
+        } = draw_mode.as_mut()
+        {
+            let color = if follower.is_some() {
+                &mut outline_mode.color
+            } else {
+                &mut fill_mode.color
+            };
+            let original = match original {
+                Some(original) => original.0,
+                None => {
+                    commands.entity(entity).insert(OriginalColor(*color));
+                    *color
+                }
+            };
+            *color = if settings.colour_blind {
+                highlight.color(&palette)
+            } else {
+                original
+            };
+        }
+    }
+}
+
+/// Colours highlighted UI nodes, like the minimap dots, from the palette.
+pub fn apply_ui_highlights(
+    settings: Res<UserSettings>,
+    mut node_query: Query<(&Highlight, &mut UiColor)>,
+    added_query: Query<(), Added<Highlight>>,
+) {
+    if !settings.is_changed() && added_query.is_empty() {
+        return;
+    }
+    let palette = settings.palette();
+    for (highlight, mut color) in &mut node_query {
+        *color = highlight.color(&palette).into();
+    }
+}
@@ -0,0 +1,132 @@
+//! Plays scripted moments of a run headlessly and checks how they end.
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::Velocity;
+use not_alone::{
+    components::{Follower, Player, Walker},
+    config::GameConfig,
+    generation::{get_buildings, Building},
+    headless::headless_app,
+    resources::RunConfig,
+    AppState,
+};
+
+/// More than enough updates for an arrival to be noticed and the state to change.
+const ARRIVAL_FRAMES: u32 = 10;
+
+struct Harness {
+    app: App,
+}
+
+impl Harness {
+    /// An empty-streeted run with one follower, already spawned.
+    fn new(configure: impl FnOnce(&mut GameConfig)) -> Self {
+        let mut config = GameConfig::default();
+        config.normal.bystander_count = 0;
+        config.normal.follower_count = 1;
+        configure(&mut config);
+        let mut app = headless_app(
+            config,
+            RunConfig {
+                seed: 1,
+                ..default()
+            },
+        );
+        app.update();
+        Harness { app }
+    }
+
+    fn building(name: &str) -> Building {
+        get_buildings(0)
+            .into_iter()
+            .find(|building| building.name == name)
+            .unwrap()
+    }
+
+    /// Makes `name` the player's destination and stands them in its entrance.
+    fn place_player_at_entrance(&mut self, name: &str) {
+        let building = Harness::building(name);
+        let entrance = building.entrance.unwrap();
+        // The bottom edge of the entrance, clear of the building itself.
+        let location = Vec2::new((entrance.min.x + entrance.max.x) / 2.0, entrance.min.y);
+        let world = &mut self.app.world;
+        let mut query = world.query::<(&mut Player, &mut Transform, &mut Velocity, &mut Walker)>();
+        for (mut player, mut transform, mut velocity, mut walker) in query.iter_mut(world) {
+            player.destination = building.clone();
+            stop_at(location, &mut transform, &mut velocity, &mut walker);
+        }
+    }
+
+    fn place_followers(&mut self, location: Vec2) {
+        let world = &mut self.app.world;
+        let mut query =
+            world.query_filtered::<(&mut Transform, &mut Velocity, &mut Walker), With<Follower>>();
+        for (mut transform, mut velocity, mut walker) in query.iter_mut(world) {
+            stop_at(location, &mut transform, &mut velocity, &mut walker);
+        }
+    }
+
+    fn state(&self) -> AppState {
+        self.app
+            .world
+            .resource::<State<AppState>>()
+            .current()
+            .clone()
+    }
+
+    /// Steps until the run ends or `frames` updates pass, and returns the state it's left in.
+    fn run(&mut self, frames: u32) -> AppState {
+        for _ in 0..frames {
+            if self.state() != AppState::Game {
+                break;
+            }
+            self.app.update();
+        }
+        self.state()
+    }
+}
+
+fn stop_at(
+    location: Vec2,
+    transform: &mut Transform,
+    velocity: &mut Velocity,
+    walker: &mut Walker,
+) {
+    transform.translation.x = location.x;
+    transform.translation.y = location.y;
+    *velocity = Velocity::default();
+    *walker = Walker::default();
+}
+
+#[test]
+fn arriving_unseen_wins() {
+    let mut harness = Harness::new(|_| {});
+    harness.place_player_at_entrance("The Tower");
+    // Well beyond the follower's vision range.
+    harness.place_followers(Vec2::new(100.0, 1000.0));
+    assert_eq!(harness.run(ARRIVAL_FRAMES), AppState::Win);
+}
+
+#[test]
+fn arriving_in_plain_sight_loses() {
+    let mut harness = Harness::new(|_| {});
+    harness.place_player_at_entrance("The Tower");
+    // Straight down the open street from the entrance.
+    harness.place_followers(Vec2::new(824.0, 450.0));
+    assert_eq!(harness.run(ARRIVAL_FRAMES), AppState::Lose);
+}
+
+#[test]
+fn arriving_with_a_building_in_the_way_wins() {
+    let mut harness = Harness::new(|_| {});
+    harness.place_player_at_entrance("The Tower");
+    // In range, but round the corner with the tower between them.
+    harness.place_followers(Vec2::new(680.0, 800.0));
+    assert_eq!(harness.run(ARRIVAL_FRAMES), AppState::Win);
+}
+
+#[test]
+fn running_out_of_time_loses() {
+    let mut harness = Harness::new(|config| config.normal.time_limit = 0.5);
+    harness.place_followers(Vec2::new(100.0, 1000.0));
+    assert_eq!(harness.run(60), AppState::Lose);
+}
@@ -0,0 +1,59 @@
+//! Checks which runs make the leaderboard, and that high scores saved by older builds still
+//! load.
+use not_alone::{
+    config::Difficulty,
+    high_scores::{HighScores, RunRecord},
+};
+
+/// As written by the first build to save high scores, before difficulties existed, when
+/// losses were ranked alongside wins.
+const VERSION_1: &str = r#"(
+    version: 1,
+    best: [
+        (map: "Downtown", seed: 7, time: 84.5, score: 1420, won: true, finished_at: 1660000000),
+        (map: "Downtown", seed: 8, time: 30.0, score: 900, won: false, finished_at: 1660000100),
+    ],
+    history: [
+        (map: "Downtown", seed: 7, time: 84.5, score: 1420, won: true, finished_at: 1660000000),
+        (map: "Downtown", seed: 8, time: 30.0, score: 900, won: false, finished_at: 1660000100),
+    ],
+)"#;
+
+#[test]
+fn version_1_scores_load_and_round_trip() {
+    let scores = ron::from_str::<HighScores>(VERSION_1).unwrap().migrate();
+    assert_eq!(scores.version, 2);
+    assert_eq!(scores.history.len(), 2);
+    assert_eq!(scores.best.len(), 1);
+    let best = &scores.best[0];
+    assert!(best.won);
+    assert_eq!(best.seed, 7);
+    assert_eq!(best.difficulty, Difficulty::Normal);
+
+    let saved = ron::to_string(&scores).unwrap();
+    let reloaded = ron::from_str::<HighScores>(&saved).unwrap().migrate();
+    assert_eq!(reloaded.version, scores.version);
+    assert_eq!(reloaded.history.len(), scores.history.len());
+    assert_eq!(reloaded.best.len(), scores.best.len());
+    assert_eq!(reloaded.best[0].score, best.score);
+    assert_eq!(reloaded.best[0].finished_at, best.finished_at);
+}
+
+#[test]
+fn losses_are_kept_out_of_the_best_runs() {
+    let mut scores = HighScores::default();
+    for (seed, won) in [(1, true), (2, false)] {
+        scores.record(RunRecord {
+            map: "Downtown".to_string(),
+            difficulty: Difficulty::Normal,
+            seed,
+            time: 60.0,
+            score: 1000,
+            won,
+            finished_at: 0,
+        });
+    }
+    assert_eq!(scores.history.len(), 2);
+    assert_eq!(scores.best.len(), 1);
+    assert!(scores.best[0].won);
+}
@@ -0,0 +1,118 @@
+//! Records a headless run and checks that playing it back gives exactly the same run.
+use bevy::prelude::*;
+use not_alone::{
+    components::Walker,
+    config::GameConfig,
+    gameplay::{AddSimulationSystems, GameplaySystem, SimulationClock, SimulationStage},
+    headless::headless_app,
+    input::PlayerInput,
+    replay::{play_back_input, record_input, Playback, RecordedInput, Recording, Replay},
+    resources::{RunConfig, RunStats},
+};
+
+/// Five seconds of simulated time.
+const STEPS: u32 = 300;
+
+/// Wanders in a slow circle, sprinting now and then, so the recording isn't one long span.
+fn wander(clock: Res<SimulationClock>, mut input: ResMut<PlayerInput>) {
+    let angle = clock.steps as f32 * 0.02;
+    input.movement = Vec2::new(angle.cos(), angle.sin()) * 0.8;
+    input.sprint = clock.steps % 90 < 30;
+}
+
+/// Where every agent is, and the run's stats, after `STEPS` updates.
+fn play(app: &mut App) -> (Vec<Vec3>, f32, u32, f32) {
+    for _ in 0..STEPS {
+        app.update();
+    }
+    let world = &mut app.world;
+    let locations = world
+        .query_filtered::<&Transform, With<Walker>>()
+        .iter(world)
+        .map(|transform| transform.translation)
+        .collect();
+    let stats = world.resource::<RunStats>();
+    (
+        locations,
+        stats.time_taken,
+        stats.times_spotted,
+        stats.closest_approach,
+    )
+}
+
+fn run_config() -> RunConfig {
+    RunConfig {
+        seed: 7,
+        ..default()
+    }
+}
+
+#[test]
+fn playing_back_a_recording_repeats_the_run() {
+    let config = GameConfig::default();
+
+    let mut recorded = headless_app(config.clone(), run_config());
+    recorded
+        .insert_resource(Recording(Replay::new(&config, &run_config())))
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new()
+                .label(GameplaySystem::Input)
+                .before(GameplaySystem::Ai)
+                .with_system(wander)
+                .with_system(record_input.after(wander)),
+        );
+    let original = play(&mut recorded);
+    let replay = recorded.world.remove_resource::<Recording>().unwrap().0;
+    assert!(replay.inputs.len() > 1);
+
+    let mut played_back = headless_app(replay.config.clone(), replay.run_config());
+    played_back
+        .insert_resource(Playback::new(replay))
+        .add_simulation_system_set(
+            SimulationStage::Update,
+            SystemSet::new().with_system(
+                play_back_input
+                    .label(GameplaySystem::Input)
+                    .before(GameplaySystem::Ai),
+            ),
+        );
+    assert_eq!(play(&mut played_back), original);
+}
+
+#[test]
+fn recordings_keep_their_input_through_a_save() {
+    let config = GameConfig::default();
+    let mut replay = Replay::new(&config, &run_config());
+    let inputs = [
+        RecordedInput::default(),
+        RecordedInput::default(),
+        RecordedInput {
+            movement: (127, -64),
+            sprint: true,
+            ..default()
+        },
+        RecordedInput::default(),
+    ];
+    for input in inputs {
+        replay.push(input);
+    }
+    assert_eq!(replay.inputs.len(), 3);
+
+    let saved = ron::to_string(&replay).unwrap();
+    let loaded: Replay = ron::from_str(&saved).unwrap();
+    assert_eq!(loaded.step_inputs(), inputs);
+    assert_eq!(loaded.step_count(), inputs.len());
+    assert!(loaded.matches(&run_config()));
+}
+
+#[test]
+fn replays_on_maps_that_dont_exist_are_refused() {
+    let mut replay = Replay::new(&GameConfig::default(), &run_config());
+    replay.map = usize::MAX;
+    let path = std::env::temp_dir().join("not_alone_unknown_map_replay.ron");
+    replay.save(&path);
+    let loaded = Replay::load(&path);
+    let _ = std::fs::remove_file(&path);
+    assert!(loaded.is_err());
+}
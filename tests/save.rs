@@ -0,0 +1,52 @@
+//! Saves a headless run part way through and checks it carries on from where it left off.
+use bevy::prelude::*;
+use not_alone::{
+    config::GameConfig,
+    headless::headless_app,
+    resources::RunConfig,
+    save::{ResumedRun, SavedRun},
+};
+
+/// Two seconds of simulated time.
+const STEPS: u32 = 120;
+
+#[test]
+fn saved_runs_carry_on_where_they_left_off() {
+    let mut config = GameConfig::default();
+    // However the followers wander, the run is still going when it's saved.
+    config.normal.suspicion_fill_rate = 0.0;
+    let mut original = headless_app(
+        config.clone(),
+        RunConfig {
+            seed: 3,
+            ..default()
+        },
+    );
+    for _ in 0..STEPS {
+        original.update();
+    }
+    let saved = SavedRun::capture(&mut original.world).unwrap();
+    // Through RON, as it would go between sessions.
+    let saved: SavedRun = ron::from_str(&ron::to_string(&saved).unwrap()).unwrap();
+
+    let mut resumed = headless_app(config, saved.run_config());
+    resumed.insert_resource(ResumedRun(saved));
+    // Both take one more step.
+    resumed.update();
+    original.update();
+
+    let expected = SavedRun::capture(&mut original.world).unwrap();
+    let actual = SavedRun::capture(&mut resumed.world).unwrap();
+    assert_eq!(actual.steps, expected.steps);
+    assert_eq!(actual.rng, expected.rng);
+    assert_eq!(actual.stats.time_taken, expected.stats.time_taken);
+    assert_eq!(actual.player.destination, expected.player.destination);
+    assert_eq!(actual.followers.len(), expected.followers.len());
+    assert_eq!(actual.bystanders.len(), expected.bystanders.len());
+    let player_offset = actual
+        .player
+        .agent
+        .translation
+        .distance(expected.player.agent.translation);
+    assert!(player_offset < 1.0, "player is {} px out", player_offset);
+}
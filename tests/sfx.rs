@@ -0,0 +1,41 @@
+//! Where sound effects are heard from.
+use bevy::prelude::*;
+use not_alone::sfx::placement;
+
+const RANGE: f32 = 100.0;
+
+#[test]
+fn sounds_are_heard_from_their_side() {
+    let listener = Vec2::ZERO;
+    let (left, right) = placement(listener, Vec2::new(50.0, 0.0), RANGE);
+    assert!(
+        right > left,
+        "right of the listener is {} left, {} right",
+        left,
+        right
+    );
+    let (left, right) = placement(listener, Vec2::new(-50.0, 0.0), RANGE);
+    assert!(
+        left > right,
+        "left of the listener is {} left, {} right",
+        left,
+        right
+    );
+    // Straight ahead, or right on top of the listener, is even.
+    let (left, right) = placement(listener, Vec2::new(0.0, 50.0), RANGE);
+    assert!((left - right).abs() < 1e-6);
+    let (left, right) = placement(listener, listener, RANGE);
+    assert!((left - right).abs() < 1e-6);
+}
+
+#[test]
+fn sounds_out_of_range_are_silent() {
+    assert_eq!(
+        placement(Vec2::ZERO, Vec2::new(RANGE, 0.0), RANGE),
+        (0.0, 0.0)
+    );
+    assert_eq!(
+        placement(Vec2::ZERO, Vec2::new(0.0, 250.0), RANGE),
+        (0.0, 0.0)
+    );
+}